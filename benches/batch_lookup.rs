@@ -0,0 +1,35 @@
+use bloomz::{BloomFilter, SeededSip13};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+fn bench_batch(c: &mut Criterion) {
+    let n = 4_000_000u64;
+    let present: Vec<u64> = (0..1_000_000u64).map(|i| (i * 7919) % n).collect();
+    let absent: Vec<u64> = (0..1_000_000u64).map(|i| n + i * 7919).collect();
+
+    let mut group = c.benchmark_group("batch_lookup");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(present.len() as u64));
+    // 8 MiB fits in most L3 caches, 256 MiB does not
+    for (label, m) in [("8MiB", 1usize << 26), ("256MiB", 1usize << 31)] {
+        let mut bf = BloomFilter::with_hasher(m, 7, SeededSip13::with_seed(1));
+        let keys: Vec<u64> = (0..n).collect();
+        bf.insert_many(&keys);
+
+        for (kind, queries) in [("present", &present), ("absent", &absent)] {
+            group.bench_with_input(BenchmarkId::new(format!("contains/{kind}"), label), queries, |b, q| {
+                b.iter(|| black_box(q.iter().map(|k| bf.contains(k)).filter(|&hit| hit).count()))
+            });
+            group.bench_with_input(BenchmarkId::new(format!("contains_many/{kind}"), label), queries, |b, q| {
+                b.iter(|| black_box(bf.contains_many(q).into_iter().filter(|&hit| hit).count()))
+            });
+        }
+        group.bench_with_input(BenchmarkId::new("insert", label), &absent, |b, q| {
+            b.iter(|| q.iter().for_each(|k| bf.insert(k)))
+        });
+        group.bench_with_input(BenchmarkId::new("insert_many", label), &absent, |b, q| b.iter(|| bf.insert_many(q)));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_batch);
+criterion_main!(benches);
@@ -0,0 +1,68 @@
+use bloomz::bitset::BitSet;
+use bloomz::BloomFilter;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+/// The word-at-a-time loops `BitSet` used before its bulk ops were vectorized.
+fn scalar_or(a: &mut [u64], b: &[u64]) {
+    for (x, y) in a.iter_mut().zip(b) {
+        *x |= *y;
+    }
+}
+
+fn scalar_count(words: &[u64]) -> usize {
+    words.iter().map(|w| w.count_ones() as usize).sum()
+}
+
+fn half_full(bits: usize, seed: u64) -> BitSet {
+    let mut set = BitSet::new(bits);
+    let mut x = seed | 1;
+    for w in set.words_mut() {
+        // xorshift64
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        *w = x;
+    }
+    set
+}
+
+fn bench_bitset(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bitset_ops");
+    group.sample_size(20);
+    for (label, bits) in [("1MiB", 1usize << 23), ("16MiB", 1usize << 27)] {
+        let (a, b) = (half_full(bits, 1), half_full(bits, 2));
+        group.throughput(Throughput::Bytes(bits as u64 / 8));
+
+        let mut dst = a.clone();
+        group.bench_function(BenchmarkId::new("or/scalar", label), |bch| {
+            bch.iter(|| scalar_or(dst.words_mut(), b.words_slice()))
+        });
+        group.bench_function(BenchmarkId::new("or/simd", label), |bch| bch.iter(|| dst.or_with(&b)));
+        group.bench_function(BenchmarkId::new("and_not/simd", label), |bch| bch.iter(|| dst.and_not_with(&b)));
+
+        group.bench_function(BenchmarkId::new("count_ones/scalar", label), |bch| {
+            bch.iter(|| black_box(scalar_count(a.words_slice())))
+        });
+        group.bench_function(BenchmarkId::new("count_ones/simd", label), |bch| bch.iter(|| black_box(a.count_ones())));
+
+        let empty = BitSet::new(bits);
+        group.bench_function(BenchmarkId::new("any_empty/scalar", label), |bch| {
+            bch.iter(|| black_box(empty.words_slice().iter().any(|&w| w != 0)))
+        });
+        group.bench_function(BenchmarkId::new("any_empty/simd", label), |bch| bch.iter(|| black_box(empty.any())));
+    }
+
+    // the filter-level entry points on a 16 MiB filter
+    let mut bf = BloomFilter::new(1 << 27, 7);
+    for i in 0..2_000_000u64 {
+        bf.insert(&i);
+    }
+    let other = bf.clone();
+    group.throughput(Throughput::Bytes(1 << 24));
+    group.bench_function("bloom/union_inplace", |bch| bch.iter(|| bf.union_inplace(&other)));
+    group.bench_function("bloom/estimated_cardinality", |bch| bch.iter(|| black_box(bf.estimated_cardinality())));
+    group.finish();
+}
+
+criterion_group!(benches, bench_bitset);
+criterion_main!(benches);
@@ -1,7 +1,7 @@
 use std::collections::hash_map::RandomState;
 use std::hash::BuildHasher;
 
-use bloomz::BloomFilter;
+use bloomz::{BloomFilter, Hash128Builder, SeededSip13};
 use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
 
 #[cfg(feature = "fast-ahash")]
@@ -55,12 +55,47 @@ fn contains_absent<H: BuildHasher + Clone + 'static>(label: &str, c: &mut Criter
     });
 }
 
+/// Hides a builder's 128-bit digest, so `with_hasher` falls back to two passes.
+#[derive(Clone)]
+struct TwoPass<H>(H);
+
+impl<H: BuildHasher> BuildHasher for TwoPass<H> {
+    type Hasher = H::Hasher;
+
+    fn build_hasher(&self) -> H::Hasher {
+        self.0.build_hasher()
+    }
+}
+
+fn insert_keys<S: BuildHasher + Clone>(name: &str, c: &mut Criterion, keys: &[String], mut bf: BloomFilter<S>) {
+    c.bench_function(name, |b| {
+        b.iter(|| {
+            for key in keys { bf.insert(key); }
+        });
+    });
+}
+
+fn insert_long_keys<H: Hash128Builder + Clone + 'static>(label: &str, c: &mut Criterion, hasher: H) {
+    let keys: Vec<String> = (0..20_000u64).map(|i| format!("{:0>200}", i)).collect();
+    let two_pass = BloomFilter::with_hasher(200_000, 7, TwoPass(hasher.clone()));
+    insert_keys(&format!("insert_200b_keys/{label}-two-pass"), c, &keys, two_pass);
+    insert_keys(&format!("insert_200b_keys/{label}-128"), c, &keys, BloomFilter::with_hasher(200_000, 7, hasher));
+}
+
 fn criterion_benchmark(c: &mut Criterion) {
     // SipHash (RandomState)
     build_and_insert("sip", c, RandomState::new());
     contains_present("sip", c, RandomState::new());
     contains_absent("sip", c, RandomState::new());
 
+    // Seeded SipHash-1-3 (portable, persistable)
+    build_and_insert("sip13-seeded", c, SeededSip13::with_seed(1));
+    contains_present("sip13-seeded", c, SeededSip13::with_seed(1));
+    contains_absent("sip13-seeded", c, SeededSip13::with_seed(1));
+
+    // One 128-bit pass vs two 64-bit passes over long keys
+    insert_long_keys("sip13", c, SeededSip13::with_seed(1));
+
     // AHash (feature fast-ahash)
     #[cfg(feature = "fast-ahash")]
     {
@@ -75,6 +110,8 @@ fn criterion_benchmark(c: &mut Criterion) {
         build_and_insert("xxh3", c, Xxh3BuildHasher::default());
         contains_present("xxh3", c, Xxh3BuildHasher::default());
         contains_absent("xxh3", c, Xxh3BuildHasher::default());
+        contains_absent("xxh3-seeded", c, bloomz::SeededXxh3::with_seed(1));
+        insert_long_keys("xxh3", c, bloomz::SeededXxh3::with_seed(1));
     }
 }
 
@@ -0,0 +1,43 @@
+use bloomz::{BloomFilter, IndexStrategy, SeededSip13};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+const STRATEGIES: [(&str, IndexStrategy); 5] = [
+    ("double", IndexStrategy::DoubleHashing),
+    ("fastrange", IndexStrategy::FastRange),
+    ("enhanced", IndexStrategy::EnhancedDoubleHashing),
+    ("triple", IndexStrategy::TripleHashing),
+    ("independent", IndexStrategy::Independent),
+];
+
+fn bench_strategies(c: &mut Criterion) {
+    let n = 100_000u64;
+    // deliberately not a power of two, so `% m` is a real division
+    let m = 1_437_759;
+    let k = 10;
+
+    let mut group = c.benchmark_group("index_strategy");
+    group.throughput(Throughput::Elements(n));
+    for (label, strategy) in STRATEGIES {
+        let mut bf = BloomFilter::with_hasher_and_strategy(m, k, SeededSip13::with_seed(1), strategy);
+        group.bench_with_input(BenchmarkId::new("insert", label), &n, |b, &n| {
+            b.iter(|| {
+                for i in 0..n {
+                    bf.insert(&i);
+                }
+            });
+        });
+        group.bench_with_input(BenchmarkId::new("contains_absent", label), &n, |b, &n| {
+            b.iter(|| {
+                let mut hits = 0u64;
+                for i in n..n * 2 {
+                    hits += bf.contains(&i) as u64;
+                }
+                black_box(hits)
+            });
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_strategies);
+criterion_main!(benches);
@@ -1,4 +1,4 @@
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
 use bloomz::BloomFilter;
 use std::collections::hash_map::RandomState;
 
@@ -114,5 +114,38 @@ fn bench_batch_sizes(c: &mut Criterion) {
     }
 }
 
-criterion_group!(benches, bench_insert_methods, bench_contains_methods, bench_batch_sizes);
+fn bench_parallel_speedup(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parallel_speedup");
+    group.sample_size(10);
+
+    let rs = RandomState::new();
+    let n = 1_000_000usize;
+    let items: Vec<u64> = (0..n as u64).collect();
+    let m = bloomz::math::optimal_m(n, 0.01);
+    let k = bloomz::math::optimal_k(m, n);
+    group.throughput(Throughput::Elements(n as u64));
+
+    group.bench_function(BenchmarkId::new("sequential_insert", n), |b| {
+        b.iter(|| {
+            let mut bf = BloomFilter::with_hasher(m, k, rs.clone());
+            for item in &items {
+                bf.insert(black_box(item));
+            }
+            bf
+        });
+    });
+
+    #[cfg(feature = "rayon")]
+    group.bench_function(BenchmarkId::new("insert_batch", n), |b| {
+        b.iter(|| {
+            let mut bf = BloomFilter::with_hasher(m, k, rs.clone());
+            bf.insert_batch(items.par_iter().cloned());
+            bf
+        });
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_insert_methods, bench_contains_methods, bench_batch_sizes, bench_parallel_speedup);
 criterion_main!(benches);
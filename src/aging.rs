@@ -0,0 +1,227 @@
+use core::hash::{BuildHasher, Hash};
+
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::{bloom::BloomFilter, math};
+
+/// Source of monotonic time for `AgingBloomFilter`.
+pub trait Clock {
+    /// Time elapsed since an arbitrary, fixed origin. Must never go backwards.
+    fn now(&self) -> Duration;
+}
+
+/// Wall clock backed by `std::time::Instant`.
+#[derive(Debug, Clone, Copy)]
+pub struct SystemClock {
+    origin: Instant,
+}
+
+impl SystemClock {
+    /// Clock whose origin is the moment of the call.
+    pub fn new() -> Self {
+        Self { origin: Instant::now() }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.origin.elapsed()
+    }
+}
+
+/// Clock that only moves when told to; clones share the same time.
+///
+/// Keep one handle and give a clone to the filter to drive expiry in tests.
+#[derive(Debug, Clone, Default)]
+pub struct ManualClock {
+    nanos: Arc<AtomicU64>,
+}
+
+impl ManualClock {
+    /// Clock starting at zero.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Move time forward by `by`.
+    pub fn advance(&self, by: Duration) {
+        self.nanos.fetch_add(by.as_nanos() as u64, Ordering::Relaxed);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        Duration::from_nanos(self.nanos.load(Ordering::Relaxed))
+    }
+}
+
+/// When an `AgingBloomFilter` starts a new generation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    /// Every time this much clock time has passed.
+    Interval(Duration),
+    /// Once the current generation has received this many inserts.
+    Inserts(usize),
+}
+
+/// Aging bloom filter: a ring of `BloomFilter` generations.
+///
+/// Inserts go into the newest generation and lookups check every live one.
+/// On rotation the oldest generation is cleared and becomes the newest, so an
+/// item expires wholesale after `g - 1` to `g` rotation periods. With
+/// `Rotation::Interval(t)` this gives "seen within the last `(g - 1) * t`"
+/// semantics without per-item timestamps.
+#[derive(Clone)]
+pub struct AgingBloomFilter<S = std::collections::hash_map::RandomState, C = SystemClock> {
+    generations: Vec<BloomFilter<S>>,
+    head: usize,
+    rotation: Rotation,
+    last_rotation: Duration,
+    clock: C,
+}
+
+impl<S, C> fmt::Debug for AgingBloomFilter<S, C>
+where
+    S: BuildHasher + Clone,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AgingBloomFilter")
+            .field("generations", &self.generations)
+            .field("head", &self.head)
+            .field("rotation", &self.rotation)
+            .finish()
+    }
+}
+
+impl AgingBloomFilter<std::collections::hash_map::RandomState, SystemClock> {
+    /// convenience constructor using default hasher builder and the system clock.
+    ///
+    /// Each generation is sized for `capacity` items at false-positive rate `p`.
+    pub fn new(generations: usize, capacity: usize, p: f64, rotation: Rotation) -> Self {
+        Self::with_clock(generations, capacity, p, rotation, std::collections::hash_map::RandomState::new(), SystemClock::new())
+    }
+}
+
+impl<S, C> AgingBloomFilter<S, C>
+where
+    S: BuildHasher + Clone,
+    C: Clock,
+{
+    /// create with explicit hasher builder and clock.
+    ///
+    /// Needs at least two generations so the newest is never the only one.
+    pub fn with_clock(generations: usize, capacity: usize, p: f64, rotation: Rotation, hasher_builder: S, clock: C) -> Self {
+        assert!(generations >= 2, "need at least two generations");
+        match rotation {
+            Rotation::Interval(t) => assert!(!t.is_zero(), "rotation interval must be > 0"),
+            Rotation::Inserts(n) => assert!(n > 0, "rotation insert count must be > 0"),
+        }
+        let m = math::optimal_m(capacity, p);
+        let k = math::optimal_k(m, capacity);
+        let generations = (0..generations)
+            .map(|_| BloomFilter::with_hasher(m, k, hasher_builder.clone()))
+            .collect();
+        let last_rotation = clock.now();
+        Self {
+            generations,
+            head: 0,
+            rotation,
+            last_rotation,
+            clock,
+        }
+    }
+
+    /// Number of interval rotations due but not yet applied (at most `g`).
+    fn pending_rotations(&self) -> usize {
+        match self.rotation {
+            Rotation::Interval(t) => {
+                let elapsed = self.clock.now().saturating_sub(self.last_rotation);
+                (elapsed.as_nanos() / t.as_nanos()).min(self.generations.len() as u128) as usize
+            }
+            Rotation::Inserts(_) => 0,
+        }
+    }
+
+    /// Apply any rotations that are due on the clock.
+    pub fn expire(&mut self) {
+        if let Rotation::Interval(t) = self.rotation {
+            let due = self.pending_rotations();
+            for _ in 0..due {
+                self.rotate();
+            }
+            // keep period boundaries aligned, even after a long idle stretch
+            let now = self.clock.now();
+            let into_period = now.saturating_sub(self.last_rotation).as_nanos() % t.as_nanos();
+            self.last_rotation = now - Duration::from_nanos(into_period as u64);
+        }
+    }
+
+    /// Start a new generation now, expiring the oldest one.
+    ///
+    /// The expired generation is cleared in place and reused.
+    pub fn rotate(&mut self) {
+        self.head = (self.head + 1) % self.generations.len();
+        self.generations[self.head].clear();
+    }
+
+    /// Insert an item into the newest generation, rotating first if due.
+    pub fn insert<T: Hash>(&mut self, item: &T) {
+        self.expire();
+        if let Rotation::Inserts(n) = self.rotation {
+            if self.generations[self.head].approximate_items() >= n {
+                self.rotate();
+            }
+        }
+        self.generations[self.head].insert(item);
+    }
+
+    /// Test whether an item was *probably* inserted within the live generations.
+    ///
+    /// Generations whose rotation is already due count as expired even if
+    /// `expire` has not run yet.
+    pub fn contains<T: Hash>(&self, item: &T) -> bool {
+        let g = self.generations.len();
+        let live = g - self.pending_rotations();
+        (0..live).any(|age| self.generations[(self.head + g - age) % g].contains(item))
+    }
+
+    /// Clear every generation.
+    pub fn clear(&mut self) {
+        for generation in &mut self.generations {
+            generation.clear();
+        }
+        self.head = 0;
+        self.last_rotation = self.clock.now();
+    }
+
+    /// Number of generations in the ring.
+    pub fn num_generations(&self) -> usize {
+        self.generations.len()
+    }
+
+    /// Generations from newest to oldest, including any awaiting expiry.
+    pub fn generations(&self) -> impl Iterator<Item = &BloomFilter<S>> {
+        let g = self.generations.len();
+        (0..g).map(move |age| &self.generations[(self.head + g - age) % g])
+    }
+
+    /// Rotation policy.
+    pub fn rotation(&self) -> Rotation {
+        self.rotation
+    }
+
+    /// Sum of `approximate_items` over the live generations.
+    pub fn approximate_items(&self) -> usize {
+        let live = self.generations.len() - self.pending_rotations();
+        self.generations().take(live).map(|g| g.approximate_items()).sum()
+    }
+}
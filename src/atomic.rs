@@ -0,0 +1,248 @@
+use core::hash::{BuildHasher, Hash};
+use core::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+use std::fmt;
+
+use crate::hashing::{self, Hash128Fn, KeyHash};
+use crate::index::IndexStrategy;
+use crate::{bitset::BitSet, bloom::{BloomError, BloomFilter}, format, math};
+
+/// Bloom filter that can be shared across threads without a lock.
+///
+/// Bits live in `AtomicU64` words and are set with `fetch_or`, so `insert`
+/// and `contains` both take `&self`. Bit positions are the same as
+/// `BloomFilter`'s (including its `IndexStrategy` and pair derivation), so
+/// the two convert into each other losslessly.
+pub struct AtomicBloomFilter<S = std::collections::hash_map::RandomState> {
+    words: Vec<AtomicU64>,
+    m: usize,
+    k: u32,
+    items: AtomicUsize,
+    strategy: IndexStrategy,
+    hash128: Option<Hash128Fn<S>>,
+    // hashing::hasher_fingerprint of hasher_builder, checked against KeyHash in debug builds
+    fingerprint: u32,
+    hasher_builder: S,
+}
+
+impl<S> fmt::Debug for AtomicBloomFilter<S>
+where
+    S: BuildHasher + Clone,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AtomicBloomFilter")
+            .field("m(bits)", &self.m)
+            .field("k", &self.k)
+            .field("items", &self.items.load(Ordering::Relaxed))
+            .finish()
+    }
+}
+
+impl AtomicBloomFilter<std::collections::hash_map::RandomState> {
+    /// convenience constructor using default hasher builder.
+    pub fn new(m: usize, k: u32) -> Self {
+        Self::with_hasher(m, k, std::collections::hash_map::RandomState::new())
+    }
+
+    /// convenience constructor from capacity and false-positive rate with default hasher.
+    pub fn new_for_capacity(n: usize, p: f64) -> Self {
+        let m = math::optimal_m(n, p);
+        let k = math::optimal_k(m, n);
+        Self::with_hasher(m, k, std::collections::hash_map::RandomState::new())
+    }
+}
+
+impl<S> AtomicBloomFilter<S>
+where
+    S: BuildHasher + Clone,
+{
+    /// create with explicit hasher builder.
+    pub fn with_hasher(m: usize, k: u32, hasher_builder: S) -> Self {
+        Self::from_bitset(BitSet::new(m), k, hasher_builder)
+    }
+
+    /// Take over the bits of an existing `BitSet` (e.g. a snapshot from `to_bitset`).
+    pub fn from_bitset(bits: BitSet, k: u32, hasher_builder: S) -> Self {
+        assert!(bits.len_bits() > 0 && k > 0 && k <= format::MAX_K);
+        Self {
+            m: bits.len_bits(),
+            words: bits.words_slice().iter().map(|&w| AtomicU64::new(w)).collect(),
+            k,
+            items: AtomicUsize::new(0),
+            strategy: IndexStrategy::default(),
+            hash128: hashing::auto_hash128::<S>(),
+            fingerprint: hashing::hasher_fingerprint(&hasher_builder),
+            hasher_builder,
+        }
+    }
+
+    /// Set the item's `k` bits; returns `true` if at least one was clear.
+    fn set_bits<T: Hash>(&self, item: &T) -> bool {
+        let mut changed = false;
+        self.strategy.probe(&self.hasher_builder, item, self.hash128, self.k, self.m, |idx| {
+            changed |= self.set_bit(idx);
+            true
+        });
+        changed
+    }
+
+    /// Set one bit; returns `true` if it was clear.
+    #[inline]
+    fn set_bit(&self, idx: usize) -> bool {
+        let mask = 1u64 << (idx % 64);
+        self.words[idx / 64].fetch_or(mask, Ordering::Relaxed) & mask == 0
+    }
+
+    #[inline]
+    fn get_bit(&self, idx: usize) -> bool {
+        self.words[idx / 64].load(Ordering::Relaxed) & (1u64 << (idx % 64)) != 0
+    }
+
+    /// Insert an item. Safe to call from many threads at once.
+    pub fn insert<T: Hash>(&self, item: &T) {
+        self.set_bits(item);
+        self.items.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Insert an item, returning `true` if it was new (not *probably* present before).
+    ///
+    /// The check and the insert are one pass of `fetch_or`s, so there is no
+    /// window in which another thread's insert can be lost. A `false` return
+    /// means every bit was already set when this call reached it. Two threads
+    /// racing to insert the same new key may both get `true`; no call for an
+    /// already present key ever does. The item counter only grows on `true`.
+    pub fn insert_if_absent<T: Hash>(&self, item: &T) -> bool {
+        let new = self.set_bits(item);
+        if new {
+            self.items.fetch_add(1, Ordering::Relaxed);
+        }
+        new
+    }
+
+    /// Test whether an item is *probably* in the set.
+    ///
+    /// Sees every insert that happens-before the call (e.g. one on a joined
+    /// thread); inserts running concurrently may or may not be visible yet.
+    pub fn contains<T: Hash>(&self, item: &T) -> bool {
+        self.strategy.probe(&self.hasher_builder, item, self.hash128, self.k, self.m, |idx| self.get_bit(idx))
+    }
+
+    /// Hash `item` once for the `_hashed` methods here or on any compatible filter (see `KeyHash`).
+    pub fn hash_key<T: Hash>(&self, item: &T) -> KeyHash {
+        KeyHash::new(hashing::hash_pair(&self.hasher_builder, item, self.hash128), self.fingerprint, self.hash128.is_some())
+    }
+
+    /// `insert` for a key hashed with `hash_key`.
+    ///
+    /// Panics as `BloomFilter::insert_hashed` does.
+    pub fn insert_hashed(&self, hash: KeyHash) {
+        hash.debug_check(self.fingerprint, self.hash128.is_some());
+        self.strategy.probe_hashed(hash, self.k, self.m, |idx| {
+            self.set_bit(idx);
+            true
+        });
+        self.items.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// `contains` for a key hashed with `hash_key`.
+    ///
+    /// Panics as `BloomFilter::insert_hashed` does.
+    pub fn contains_hashed(&self, hash: KeyHash) -> bool {
+        hash.debug_check(self.fingerprint, self.hash128.is_some());
+        self.strategy.probe_hashed(hash, self.k, self.m, |idx| self.get_bit(idx))
+    }
+
+    /// Clear all bits and reset the item counter to zero.
+    ///
+    /// Needs `&mut self`: clearing under concurrent inserts would leave a
+    /// torn filter.
+    pub fn clear(&mut self) {
+        for w in &mut self.words {
+            *w.get_mut() = 0;
+        }
+        *self.items.get_mut() = 0;
+    }
+
+    /// Approximate number of inserts (see `insert_if_absent` for how it counts).
+    pub fn approximate_items(&self) -> usize {
+        self.items.load(Ordering::Relaxed)
+    }
+
+    /// Number of bits (`m`).
+    pub fn num_bits(&self) -> usize {
+        self.m
+    }
+
+    /// Number of hash functions (`k`).
+    pub fn num_hashes(&self) -> u32 {
+        self.k
+    }
+
+    /// Copy the current bits into a plain `BitSet`.
+    ///
+    /// Each word is read atomically; concurrent inserts may be partially included.
+    pub fn to_bitset(&self) -> BitSet {
+        let words = self.words.iter().map(|w| w.load(Ordering::Relaxed)).collect();
+        BitSet::from_words(self.m, words)
+    }
+
+    /// Snapshot into a plain `BloomFilter` sharing this filter's hasher.
+    pub fn to_bloom_filter(&self) -> BloomFilter<S> {
+        let mut bf = BloomFilter::from_parts(self.to_bitset(), self.k, self.approximate_items(), self.hasher_builder.clone());
+        bf.set_index_strategy(self.strategy);
+        bf.set_hash128(self.hash128);
+        bf
+    }
+
+    /// Convert into a plain `BloomFilter` once no other thread holds a reference.
+    pub fn into_bloom_filter(self) -> BloomFilter<S> {
+        let words = self.words.into_iter().map(AtomicU64::into_inner).collect();
+        let mut bf = BloomFilter::from_parts(BitSet::from_words(self.m, words), self.k, self.items.into_inner(), self.hasher_builder);
+        bf.set_index_strategy(self.strategy);
+        bf.set_hash128(self.hash128);
+        bf
+    }
+
+    /// Serialize a snapshot of the filter in the `BloomFilter::to_bytes` layout.
+    ///
+    /// Panics as `BloomFilter::to_bytes` does.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.to_bloom_filter().to_bytes()
+    }
+
+    /// Fallible `to_bytes`, see `BloomFilter::try_to_bytes`.
+    pub fn try_to_bytes(&self) -> Result<Vec<u8>, BloomError> {
+        self.to_bloom_filter().try_to_bytes()
+    }
+
+    /// Deserialize from the `BloomFilter::to_bytes` layout with an explicit hasher builder.
+    ///
+    /// Returns `None` if the data length or internal layout is invalid.
+    pub fn from_bytes_hasher(data: &[u8], hasher_builder: S) -> Option<Self> {
+        BloomFilter::from_bytes_hasher(data, hasher_builder).map(Self::from)
+    }
+}
+
+impl<S> From<BloomFilter<S>> for AtomicBloomFilter<S>
+where
+    S: BuildHasher + Clone,
+{
+    fn from(filter: BloomFilter<S>) -> Self {
+        let (strategy, hash128) = (filter.index_strategy(), filter.hash128());
+        let (bits, k, items, hasher_builder) = filter.into_parts();
+        let mut atomic = Self::from_bitset(bits, k, hasher_builder);
+        *atomic.items.get_mut() = items;
+        atomic.strategy = strategy;
+        atomic.hash128 = hash128;
+        atomic
+    }
+}
+
+impl<S> From<AtomicBloomFilter<S>> for BloomFilter<S>
+where
+    S: BuildHasher + Clone,
+{
+    fn from(filter: AtomicBloomFilter<S>) -> Self {
+        filter.into_bloom_filter()
+    }
+}
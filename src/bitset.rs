@@ -1,4 +1,29 @@
 #[cfg(feature = "serde")] use serde::{Serialize, Deserialize};
+use std::io::{self, Read, Write};
+
+use crate::format::Crc32c;
+use crate::simd::{self, Op};
+
+/// Words converted per I/O chunk when streaming (64 KiB of buffer).
+const STREAM_CHUNK_WORDS: usize = 8192;
+
+/// Prefetch the cache line holding `p` into all cache levels.
+#[inline(always)]
+fn prefetch_read<T>(p: &T) {
+    #[cfg(target_arch = "x86_64")]
+    // SAFETY: a prefetch never faults and has no architectural effect; `p` is a valid reference anyway.
+    unsafe {
+        use core::arch::x86_64::{_mm_prefetch, _MM_HINT_T0};
+        _mm_prefetch::<_MM_HINT_T0>(p as *const T as *const i8);
+    }
+    #[cfg(target_arch = "aarch64")]
+    // SAFETY: as above; `prfm` only hints the memory system.
+    unsafe {
+        core::arch::asm!("prfm pldl1keep, [{0}]", in(reg) p as *const T, options(nostack, readonly, preserves_flags));
+    }
+    #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+    let _ = p;
+}
 
 /// Compact fixed-size bit set storing bits in a Vec<u64>.
 ///
@@ -20,13 +45,18 @@ impl BitSet{
     }
 
     /// Construct directly from a words vector (length must match bits.div_ceil(64)).
-    #[allow(dead_code)]
     pub(crate) fn from_words(bits: usize, words: Vec<u64>) -> Self {
         let expected = bits.div_ceil(64);
         assert_eq!(words.len(), expected, "words length mismatch for bits");
         Self { words, bits }
     }
 
+    /// Take the words vector out of the set.
+    #[allow(dead_code)]
+    pub(crate) fn into_words(self) -> Vec<u64> {
+        self.words
+    }
+
     /// Set (turn on) the bit at global index `idx`.
     /// Panics if `idx >= self.bits`.
     pub fn set(&mut self, idx : usize){
@@ -43,20 +73,48 @@ impl BitSet{
         (self.words[word] >> bit) & 1u64 == 1u64
     }
 
+    /// Hint the CPU to start loading the word holding bit `idx` into cache.
+    ///
+    /// Only a performance hint: out-of-range indexes are ignored, and on
+    /// targets without a prefetch instruction this does nothing.
+    #[inline]
+    pub(crate) fn prefetch(&self, idx: usize) {
+        if let Some(word) = self.words.get(idx / 64) {
+            prefetch_read(word);
+        }
+    }
+
     /// In-place bitwise OR with another BitSet (sizes must match).
+    ///
+    /// This and the other bulk operations use AVX2 or SSE2 on x86_64,
+    /// picked at runtime, and a portable loop elsewhere.
     pub fn or_with(&mut self, other : &BitSet){
-        assert_eq!(self.words.len(), other.words.len(), "bitset size mismatch");
-         for (a, b) in self.words.iter_mut().zip(other.words.iter()) {
-            *a |= *b;
-         }
+        simd::combine(Op::Or, &mut self.words, &other.words);
     }
 
     /// In-place bitwise AND with another BitSet (sizes must match).
     pub fn and_with(&mut self, other : &BitSet){
-        assert_eq!(self.words.len(), other.words.len(), "bitset size mismatch");
-         for (a, b) in self.words.iter_mut().zip(other.words.iter()) {
-            *a &= *b;
-         }
+        simd::combine(Op::And, &mut self.words, &other.words);
+    }
+
+    /// In-place bitwise XOR with another BitSet (sizes must match).
+    pub fn xor_with(&mut self, other : &BitSet){
+        simd::combine(Op::Xor, &mut self.words, &other.words);
+    }
+
+    /// In-place difference: clear every bit that is set in `other` (sizes must match).
+    pub fn and_not_with(&mut self, other : &BitSet){
+        simd::combine(Op::AndNot, &mut self.words, &other.words);
+    }
+
+    /// Number of set bits.
+    pub fn count_ones(&self) -> usize {
+        simd::count_ones(&self.words)
+    }
+
+    /// Whether any bit is set. Stops scanning at the first set bit.
+    pub fn any(&self) -> bool {
+        simd::any(&self.words)
     }
 
     /// Clear all bits (set to 0).
@@ -78,4 +136,71 @@ impl BitSet{
     pub fn words_mut(&mut self) -> &mut [u64] {
         &mut self.words
     }
+
+    /// Stream the set to `w`: bit length (u64 LE) then the words (u64 LE).
+    ///
+    /// Uses a fixed 64 KiB buffer regardless of the set size.
+    pub fn write_to<W: Write>(&self, mut w: W) -> io::Result<()> {
+        w.write_all(&(self.bits as u64).to_le_bytes())?;
+        write_words(&mut w, &self.words, None)
+    }
+
+    /// Read a set written by `write_to`.
+    ///
+    /// Fails with `UnexpectedEof` if the input is truncated and `InvalidData`
+    /// if padding bits past the length are set.
+    pub fn read_from<R: Read>(mut r: R) -> io::Result<Self> {
+        let mut len = [0u8; 8];
+        r.read_exact(&mut len)?;
+        let bits = usize::try_from(u64::from_le_bytes(len))
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "bitset length overflows usize"))?;
+        let words = read_words(&mut r, bits, None)?;
+        Ok(Self { words, bits })
+    }
+}
+
+/// Write `words` little-endian through a bounded buffer, feeding `crc` if given.
+pub(crate) fn write_words<W: Write>(w: &mut W, words: &[u64], mut crc: Option<&mut Crc32c>) -> io::Result<()> {
+    let mut buf = Vec::with_capacity(STREAM_CHUNK_WORDS.min(words.len()) * 8);
+    for chunk in words.chunks(STREAM_CHUNK_WORDS) {
+        buf.clear();
+        for word in chunk {
+            buf.extend_from_slice(&word.to_le_bytes());
+        }
+        if let Some(crc) = crc.as_deref_mut() {
+            crc.update(&buf);
+        }
+        w.write_all(&buf)?;
+    }
+    Ok(())
+}
+
+/// Read the words of a `bits`-bit set through a bounded buffer, feeding `crc` if given.
+pub(crate) fn read_words<R: Read>(r: &mut R, bits: usize, mut crc: Option<&mut Crc32c>) -> io::Result<Vec<u64>> {
+    let n = bits.div_ceil(64);
+    let mut words: Vec<u64> = Vec::new();
+    let mut buf = vec![0u8; STREAM_CHUNK_WORDS.min(n) * 8];
+    let mut left = n;
+    while left > 0 {
+        let take = left.min(STREAM_CHUNK_WORDS);
+        let bytes = &mut buf[..take * 8];
+        r.read_exact(bytes)?;
+        // `n` comes from the input: grow only as words arrive (doubling, capped
+        // at `n`), so a corrupt length hits EOF instead of a huge allocation
+        if words.capacity() - words.len() < take {
+            let extra = words.capacity().max(take).min(left);
+            words
+                .try_reserve_exact(extra)
+                .map_err(|_| io::Error::new(io::ErrorKind::OutOfMemory, "bitset too large to allocate"))?;
+        }
+        if let Some(crc) = crc.as_deref_mut() {
+            crc.update(bytes);
+        }
+        words.extend(bytes.chunks_exact(8).map(|c| u64::from_le_bytes(c.try_into().unwrap())));
+        left -= take;
+    }
+    if !bits.is_multiple_of(64) && words[n - 1] >> (bits % 64) != 0 {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "bits set past the bitset length"));
+    }
+    Ok(words)
 }
@@ -5,7 +5,92 @@ use std::fmt;
 #[cfg(feature = "serde")] use serde::{Deserialize, Serializer, Deserializer, ser::SerializeStruct};
 #[cfg(feature = "rayon")] use rayon::prelude::*;
 
-use crate::{bitset::BitSet, hashing, math};
+use std::io::{self, Read, Write};
+
+use crate::{bitset::{self, BitSet}, hashing, math};
+use crate::hashing::{Hash128Builder, Hash128Fn, KeyHash};
+use crate::index::IndexStrategy;
+use crate::format::{self, Crc32c, FilterKind, FormatError, Header, CHECKSUM_LEN, HEADER_LEN};
+use crate::seeded::PersistentHasher;
+/// Error returned by the fallible (`try_*`) constructors, set operations and deserializers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BloomError {
+    /// Constructor parameters are out of range; the message names the offending one.
+    InvalidParams(&'static str),
+    /// A set operation was given a filter with different `(m, k)`.
+    SizeMismatch {
+        /// `(m, k)` of the receiving filter.
+        expected: (usize, u32),
+        /// `(m, k)` of the other filter.
+        found: (usize, u32),
+    },
+    /// The other filter or the data uses a different hasher builder.
+    HasherMismatch,
+    /// A set operation was given a filter with a different `IndexStrategy`,
+    /// or `try_to_bytes` a filter the legacy layout cannot describe.
+    StrategyMismatch,
+    /// The data ends before its layout says it should.
+    Truncated,
+    /// The stored checksum does not match the contents.
+    BadChecksum,
+    /// The data was written by an unsupported format version.
+    UnsupportedVersion(u16),
+    /// The data is not a bloom filter layout this crate can read.
+    Corrupt,
+}
+
+impl fmt::Display for BloomError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BloomError::InvalidParams(why) => write!(f, "invalid parameters: {}", why),
+            BloomError::SizeMismatch { expected, found } => write!(
+                f,
+                "filter size mismatch: expected m={} k={}, found m={} k={}",
+                expected.0, expected.1, found.0, found.1
+            ),
+            BloomError::HasherMismatch => write!(f, "hasher builder mismatch"),
+            BloomError::StrategyMismatch => write!(f, "index strategy mismatch"),
+            BloomError::Truncated => write!(f, "data truncated"),
+            BloomError::BadChecksum => write!(f, "checksum mismatch"),
+            BloomError::UnsupportedVersion(v) => write!(f, "unsupported format version {}", v),
+            BloomError::Corrupt => write!(f, "corrupt filter data"),
+        }
+    }
+}
+
+impl std::error::Error for BloomError {}
+
+impl From<FormatError> for BloomError {
+    fn from(e: FormatError) -> Self {
+        match e {
+            FormatError::HasherMismatch { .. } | FormatError::FingerprintMismatch | FormatError::MissingHasher => {
+                BloomError::HasherMismatch
+            }
+            FormatError::Truncated => BloomError::Truncated,
+            FormatError::BadChecksum => BloomError::BadChecksum,
+            FormatError::UnsupportedVersion(v) => BloomError::UnsupportedVersion(v),
+            FormatError::BadMagic
+            | FormatError::WrongKind(_)
+            | FormatError::UnknownStrategy(_)
+            | FormatError::InvalidLayout => BloomError::Corrupt,
+        }
+    }
+}
+
+/// Keys hashed and prefetched together by `contains_many` and `insert_many`.
+const PREFETCH_WINDOW: usize = 32;
+
+/// Check capacity-based sizing inputs before they reach `math`, which asserts.
+fn check_capacity(n: usize, p: f64) -> Result<(), BloomError> {
+    if n == 0 {
+        return Err(BloomError::InvalidParams("capacity must be > 0"));
+    }
+    if !(p > 0.0 && p < 1.0) {
+        return Err(BloomError::InvalidParams("false-positive rate must be in (0, 1)"));
+    }
+    Ok(())
+}
+
 /// bloom filter with configurable BuildHasher `S`.
 ///
 /// `S` defaults to `std::collections::hash_map::RandomState` which uses SipHash (safe).
@@ -15,6 +100,13 @@ pub struct BloomFilter<S = std::collections::hash_map::RandomState> {
     m: usize, //number of bits
     k: u32,   //hash funcs
     items: usize,
+    // (capacity, fpr) the filter was sized for; (0, 0.0) when built from raw m/k
+    design: (usize, f64),
+    // hashing::hasher_fingerprint of hasher_builder, compared by set ops and readers
+    fingerprint: u32,
+    strategy: IndexStrategy,
+    // set for 128-bit builders: probe pairs come from one digest (see hashing::hash_pair)
+    hash128: Option<Hash128Fn<S>>,
     hasher_builder: S,
     _marker: PhantomData<S>,
 }
@@ -40,9 +132,17 @@ impl BloomFilter<std::collections::hash_map::RandomState> {
 
     /// convenience constructor from capacity and false-positive rate with default hasher.
     pub fn new_for_capacity(n: usize, p: f64) -> Self {
-        let m = math::optimal_m(n, p);
-        let k = math::optimal_k(m, n);
-        Self::with_hasher(m, k, std::collections::hash_map::RandomState::new())
+        Self::for_capacity_with_hasher(n, p, std::collections::hash_map::RandomState::new())
+    }
+
+    /// Fallible `new`: errors instead of panicking when `m` or `k` is zero.
+    pub fn try_new(m: usize, k: u32) -> Result<Self, BloomError> {
+        Self::try_with_hasher(m, k, std::collections::hash_map::RandomState::new())
+    }
+
+    /// Fallible `new_for_capacity`: errors on `n == 0` or `p` outside `(0, 1)`.
+    pub fn try_new_for_capacity(n: usize, p: f64) -> Result<Self, BloomError> {
+        Self::try_for_capacity_with_hasher(n, p, std::collections::hash_map::RandomState::new())
     }
 }
 
@@ -51,74 +151,186 @@ where
     S: BuildHasher + Clone,
 {
     /// create with explicit hasher builder (eg. ahash::AHasherBuilder or RandomState)
+    ///
+    /// Panics if `m` or `k` is zero, or `k` exceeds `format::MAX_K`.
+    ///
+    /// With the crate's 128-bit builders (`SeededSip13`, `SeededXxh3`) both
+    /// probe hashes come from one digest of the item (see `with_hasher_128`).
     pub fn with_hasher(m: usize, k: u32, hasher_builder: S) -> Self {
-        assert!(m > 0 && k > 0);
+        assert!(m > 0 && k > 0 && k <= format::MAX_K);
         Self {
             bits: BitSet::new(m),
             m,
             k,
             items: 0,
+            design: (0, 0.0),
+            fingerprint: hashing::hasher_fingerprint(&hasher_builder),
+            strategy: IndexStrategy::default(),
+            hash128: hashing::auto_hash128::<S>(),
             hasher_builder,
             _marker: PhantomData,
         }
     }
 
+    /// create with explicit hasher builder and index strategy (see `IndexStrategy`).
+    ///
+    /// `with_hasher` uses `IndexStrategy::DoubleHashing`, the scheme filters
+    /// written by earlier versions rely on.
+    ///
+    /// **`to_bytes` panics** for any other strategy; serialize such a filter
+    /// with `to_versioned_bytes`, or check with `try_to_bytes`.
+    pub fn with_hasher_and_strategy(m: usize, k: u32, hasher_builder: S, strategy: IndexStrategy) -> Self {
+        let mut bf = Self::with_hasher(m, k, hasher_builder);
+        bf.strategy = strategy;
+        bf
+    }
+
+    /// create for capacity `n` and false-positive rate `p` with explicit hasher builder.
+    ///
+    /// The design parameters are kept and written by `to_versioned_bytes`.
+    pub fn for_capacity_with_hasher(n: usize, p: f64, hasher_builder: S) -> Self {
+        let m = math::optimal_m(n, p);
+        let k = math::optimal_k(m, n);
+        let mut bf = Self::with_hasher(m, k, hasher_builder);
+        bf.design = (n, p);
+        bf
+    }
+
+    /// Fallible `with_hasher`: errors instead of panicking when `m` or `k` is out of range.
+    pub fn try_with_hasher(m: usize, k: u32, hasher_builder: S) -> Result<Self, BloomError> {
+        if m == 0 {
+            return Err(BloomError::InvalidParams("m must be > 0"));
+        }
+        if k == 0 {
+            return Err(BloomError::InvalidParams("k must be > 0"));
+        }
+        if k > format::MAX_K {
+            return Err(BloomError::InvalidParams("k must be at most format::MAX_K"));
+        }
+        Ok(Self::with_hasher(m, k, hasher_builder))
+    }
+
+    /// Fallible `for_capacity_with_hasher`: errors on `n == 0` or `p` outside `(0, 1)`.
+    pub fn try_for_capacity_with_hasher(n: usize, p: f64, hasher_builder: S) -> Result<Self, BloomError> {
+        check_capacity(n, p)?;
+        Ok(Self::for_capacity_with_hasher(n, p, hasher_builder))
+    }
+
+    /// Assemble a filter from an already populated bit set.
+    pub(crate) fn from_parts(bits: BitSet, k: u32, items: usize, hasher_builder: S) -> Self {
+        assert!(bits.len_bits() > 0 && k > 0 && k <= format::MAX_K);
+        Self {
+            m: bits.len_bits(),
+            bits,
+            k,
+            items,
+            design: (0, 0.0),
+            fingerprint: hashing::hasher_fingerprint(&hasher_builder),
+            strategy: IndexStrategy::default(),
+            hash128: None,
+            hasher_builder,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Split into bit set, `k`, item counter and hasher builder.
+    pub(crate) fn into_parts(self) -> (BitSet, u32, usize, S) {
+        (self.bits, self.k, self.items, self.hasher_builder)
+    }
+
+    /// Replace the index strategy of a filter assembled with `from_parts`.
+    pub(crate) fn set_index_strategy(&mut self, strategy: IndexStrategy) {
+        self.strategy = strategy;
+    }
+
+    /// 128-bit digest probe pairs come from, if the filter hashes in a single pass.
+    pub(crate) fn hash128(&self) -> Option<Hash128Fn<S>> {
+        self.hash128
+    }
+
+    /// Replace the pair derivation of a filter assembled with `from_parts`.
+    pub(crate) fn set_hash128(&mut self, hash128: Option<Hash128Fn<S>>) {
+        self.hash128 = hash128;
+    }
+
+    /// What bit positions depend on besides the hasher: index strategy and single-pass hashing.
+    pub(crate) fn pair_derivation(&self) -> (IndexStrategy, bool) {
+        (self.strategy, self.hash128.is_some())
+    }
+
     /// Insert an item into the Bloom filter.
     ///
-    /// Computes `k` indices using double hashing based on two base hashes and
-    /// sets the corresponding bits. Duplicate inserts still increment the
+    /// Computes `k` indices with the filter's `IndexStrategy` and sets the
+    /// corresponding bits. Duplicate inserts still increment the
     /// internal `items` counter (no attempt to de-duplicate inputs is made).
     ///
     /// * `item` - The value to insert (any type implementing `Hash`).
     pub fn insert<T : Hash>(&mut self, item : &T){
-        let (h1, h2) = hashing::hash2(&self.hasher_builder, item);
-        for i in 0..self.k{
-            let combined = h1.wrapping_add((i as u64).wrapping_mul(h2));
-            let idx = (combined % (self.m as u64)) as usize;
-            self.bits.set(idx);
-        }
+        let bits = &mut self.bits;
+        self.strategy.probe(&self.hasher_builder, item, self.hash128, self.k, self.m, |idx| {
+            bits.set(idx);
+            true
+        });
+        self.items = self.items.saturating_add(1);
+    }
+
+    /// Insert reusing an already computed `hashing::hash_pair` of `item` where the strategy allows.
+    pub(crate) fn insert_prehashed<T: Hash>(&mut self, item: &T, hashes: (u64, u64)) {
+        let bits = &mut self.bits;
+        self.strategy.probe_prehashed(&self.hasher_builder, item, hashes, self.k, self.m, |idx| {
+            bits.set(idx);
+            true
+        });
         self.items = self.items.saturating_add(1);
     }
 
     /// Parallel batch insert using rayon (requires "rayon" feature).
     ///
-    /// Uses a two-phase approach: compute indices in parallel,
-    /// then merge results to avoid data races on the bit array.
+    /// The bit words are viewed as `AtomicU64`s for the duration of the call
+    /// (reusing the same allocation) and every thread sets its items' bits
+    /// with `fetch_or`, so there is no shared lock and no intermediate index
+    /// buffer. The item counter grows by the number of items in the batch,
+    /// exactly as if each had been passed to `insert`.
     #[cfg(feature = "rayon")]
     pub fn insert_batch<T>(&mut self, items: impl IntoParallelIterator<Item = T>)
     where
         T: Hash + Send + Sync,
         S: Send + Sync,
     {
-        use std::collections::HashSet;
-        use std::sync::Mutex;
-        
-        // Phase 1: Compute all indices in parallel and collect
-        let indices_set = Mutex::new(HashSet::new());
-        
-        items.into_par_iter().for_each(|item| {
-            let (h1, h2) = hashing::hash2(&self.hasher_builder, &item);
-            let mut local_indices = Vec::with_capacity(self.k as usize);
-            
-            for i in 0..self.k {
-                let combined = h1.wrapping_add((i as u64).wrapping_mul(h2));
-                let idx = (combined % (self.m as u64)) as usize;
-                local_indices.push(idx);
-            }
-            
-            let mut set = indices_set.lock().unwrap();
-            set.extend(local_indices);
-        });
+        use core::sync::atomic::{AtomicU64, Ordering};
 
-        // Phase 2: Set bits sequentially (no data races)
-        let indices = indices_set.into_inner().unwrap();
-        let count = indices.len();
-        
-        for idx in indices {
-            self.bits.set(idx);
+        // puts the words back even if a hasher panics mid-batch
+        struct Restore<'a> {
+            bits: &'a mut BitSet,
+            m: usize,
+            words: Vec<AtomicU64>,
         }
-        
-        // Update items count (approximate, since we deduplicated indices)
+        impl Drop for Restore<'_> {
+            fn drop(&mut self) {
+                let words = core::mem::take(&mut self.words).into_iter().map(AtomicU64::into_inner).collect();
+                *self.bits = BitSet::from_words(self.m, words);
+            }
+        }
+
+        let (m, k, strategy, hash128, hasher_builder) = (self.m, self.k, self.strategy, self.hash128, &self.hasher_builder);
+        let words = core::mem::replace(&mut self.bits, BitSet::new(0)).into_words();
+        let guard = Restore {
+            bits: &mut self.bits,
+            m,
+            words: words.into_iter().map(AtomicU64::new).collect(),
+        };
+
+        let count = items
+            .into_par_iter()
+            .map(|item| {
+                strategy.probe(hasher_builder, &item, hash128, k, m, |idx| {
+                    guard.words[idx / 64].fetch_or(1u64 << (idx % 64), Ordering::Relaxed);
+                    true
+                });
+            })
+            .count();
+
+        drop(guard);
         self.items = self.items.saturating_add(count);
     }
 
@@ -128,15 +340,112 @@ where
     /// (definitely not present). Returns `true` if all are set (item was
     /// likely inserted earlier, with a chance of false positives).
     pub fn contains<T : Hash>(&self, item : &T) -> bool{
-        let (h1, h2) = hashing::hash2(&self.hasher_builder, item);
-       for i in 0..self.k {
-            let combined = h1.wrapping_add((i as u64).wrapping_mul(h2));
-            let idx = (combined % (self.m as u64)) as usize;
-            if !self.bits.get(idx) {
-                return false;
+        self.strategy.probe(&self.hasher_builder, item, self.hash128, self.k, self.m, |idx| self.bits.get(idx))
+    }
+
+    /// Membership test reusing an already computed `hashing::hash_pair` of `item` where the strategy allows.
+    pub(crate) fn contains_prehashed<T: Hash>(&self, item: &T, hashes: (u64, u64)) -> bool {
+        self.strategy
+            .probe_prehashed(&self.hasher_builder, item, hashes, self.k, self.m, |idx| self.bits.get(idx))
+    }
+
+    /// Test many items at once, overlapping their cache misses; same answers as mapping `contains`.
+    ///
+    /// Items are processed in windows of 32 keys. Every key in a window is
+    /// hashed and the word of its first bit is prefetched. Keys whose first
+    /// bit is set then get the rest of their words prefetched, and only then
+    /// are any bits tested. On filters much larger than the CPU caches the
+    /// misses of a window are served in parallel instead of one after
+    /// another. For filters that fit in cache, plain `contains` is slightly
+    /// faster.
+    pub fn contains_many<T: Hash>(&self, items: &[T]) -> Vec<bool> {
+        let mut out = Vec::with_capacity(items.len());
+        self.probe_windows(items, |_, present| out.push(present));
+        out
+    }
+
+    /// `contains_many` as a bitmask: bit `i % 64` of word `i / 64` is set if `items[i]` is probably present.
+    pub fn contains_many_mask<T: Hash>(&self, items: &[T]) -> Vec<u64> {
+        let mut out = vec![0u64; items.len().div_ceil(64)];
+        self.probe_windows(items, |i, present| out[i / 64] |= (present as u64) << (i % 64));
+        out
+    }
+
+    /// Insert many items, hashing and prefetching them a window at a time like `contains_many`.
+    ///
+    /// Same result as calling `insert` for each item, duplicates included.
+    pub fn insert_many<T: Hash>(&mut self, items: &[T]) {
+        let mut positions = Vec::with_capacity(PREFETCH_WINDOW * self.k as usize);
+        for window in items.chunks(PREFETCH_WINDOW) {
+            self.window_positions(window, &mut positions);
+            positions.iter().for_each(|&idx| self.bits.prefetch(idx));
+            for &idx in &positions {
+                self.bits.set(idx);
             }
         }
-        true
+        self.items = self.items.saturating_add(items.len());
+    }
+
+    fn probe_windows<T: Hash>(&self, items: &[T], mut report: impl FnMut(usize, bool)) {
+        let k = self.k as usize;
+        let mut positions = Vec::with_capacity(PREFETCH_WINDOW * k);
+        for (w, window) in items.chunks(PREFETCH_WINDOW).enumerate() {
+            self.window_positions(window, &mut positions);
+            for probes in positions.chunks_exact(k) {
+                self.bits.prefetch(probes[0]);
+            }
+            // most absent keys are settled by their first bit; only fetch the rest for keys that pass it
+            for probes in positions.chunks_exact(k) {
+                if self.bits.get(probes[0]) {
+                    probes[1..].iter().for_each(|&idx| self.bits.prefetch(idx));
+                }
+            }
+            for (i, probes) in positions.chunks_exact(k).enumerate() {
+                report(w * PREFETCH_WINDOW + i, probes.iter().all(|&idx| self.bits.get(idx)));
+            }
+        }
+    }
+
+    /// Fill `positions` with the `k` bit positions of each key in `window`.
+    fn window_positions<T: Hash>(&self, window: &[T], positions: &mut Vec<usize>) {
+        positions.clear();
+        for item in window {
+            self.strategy.probe(&self.hasher_builder, item, self.hash128, self.k, self.m, |idx| {
+                positions.push(idx);
+                true
+            });
+        }
+    }
+
+    /// Hash `item` once for `insert_hashed`/`contains_hashed` on this or any compatible filter.
+    ///
+    /// Worth it when one key is probed against many filters built with the
+    /// same hasher builder; see `KeyHash` for what must match.
+    pub fn hash_key<T: Hash>(&self, item: &T) -> KeyHash {
+        KeyHash::new(hashing::hash_pair(&self.hasher_builder, item, self.hash128), self.fingerprint, self.hash128.is_some())
+    }
+
+    /// Insert a key hashed with `hash_key`; same effect as `insert` with the original item.
+    ///
+    /// Panics if the filter uses `IndexStrategy::Independent`, whose probes
+    /// are hashed from the item itself. Debug builds also panic on a hash
+    /// made with a different hasher builder.
+    pub fn insert_hashed(&mut self, hash: KeyHash) {
+        hash.debug_check(self.fingerprint, self.hash128.is_some());
+        let bits = &mut self.bits;
+        self.strategy.probe_hashed(hash, self.k, self.m, |idx| {
+            bits.set(idx);
+            true
+        });
+        self.items = self.items.saturating_add(1);
+    }
+
+    /// Membership test for a key hashed with `hash_key`; same answer as `contains` with the original item.
+    ///
+    /// Panics under the same conditions as `insert_hashed`.
+    pub fn contains_hashed(&self, hash: KeyHash) -> bool {
+        hash.debug_check(self.fingerprint, self.hash128.is_some());
+        self.strategy.probe_hashed(hash, self.k, self.m, |idx| self.bits.get(idx))
     }
 
     /// Parallel batch contains check (requires "rayon" feature).
@@ -165,22 +474,76 @@ where
 
     /// In‑place union (bitwise OR) with another filter.
     ///
-    /// Both filters must have identical `m` and `k` parameters.
+    /// Both filters must have identical `m` and `k` parameters and equivalent hasher builders.
     pub fn union_inplace(&mut self, other: &Self) {
         assert_eq!(self.m, other.m, "m mismatch for union");
         assert_eq!(self.k, other.k, "k mismatch for union");
+        assert_eq!(self.fingerprint, other.fingerprint, "hasher mismatch for union");
+        assert_eq!(self.pair_derivation(), other.pair_derivation(), "index strategy mismatch for union");
         self.bits.or_with(&other.bits);
     }
 
+    /// Fallible `union_inplace`: leaves `self` untouched and errors on an `m`/`k` or hasher mismatch.
+    pub fn try_union(&mut self, other: &Self) -> Result<(), BloomError> {
+        self.check_compatible(other)?;
+        self.bits.or_with(&other.bits);
+        Ok(())
+    }
+
     /// In‑place intersection (bitwise AND) with another filter.
     ///
-    /// Both filters must have identical `m` and `k` parameters.
+    /// Both filters must have identical `m` and `k` parameters and equivalent hasher builders.
     pub fn intersect_inplace(&mut self, other: &Self) {
         assert_eq!(self.m, other.m, "m mismatch for intersection");
         assert_eq!(self.k, other.k, "k mismatch for intersection");
+        assert_eq!(self.fingerprint, other.fingerprint, "hasher mismatch for intersection");
+        assert_eq!(self.pair_derivation(), other.pair_derivation(), "index strategy mismatch for intersection");
         self.bits.and_with(&other.bits);
     }
 
+    /// Fallible `intersect_inplace`: leaves `self` untouched and errors on an `m`/`k` or hasher mismatch.
+    pub fn try_intersect(&mut self, other: &Self) -> Result<(), BloomError> {
+        self.check_compatible(other)?;
+        self.bits.and_with(&other.bits);
+        Ok(())
+    }
+
+    fn check_compatible(&self, other: &Self) -> Result<(), BloomError> {
+        if self.m != other.m || self.k != other.k {
+            return Err(BloomError::SizeMismatch { expected: (self.m, self.k), found: (other.m, other.k) });
+        }
+        if self.fingerprint != other.fingerprint {
+            return Err(BloomError::HasherMismatch);
+        }
+        if self.pair_derivation() != other.pair_derivation() {
+            return Err(BloomError::StrategyMismatch);
+        }
+        Ok(())
+    }
+
+    /// Whether `other` can be merged with this filter: same `m`, `k`, hasher and index strategy.
+    ///
+    /// A `with_hasher_128` filter derives its positions differently from a
+    /// `with_hasher` one of the same builder unless that builder is one of
+    /// the crate's own 128-bit builders, and only merges with its own kind.
+    ///
+    /// Hasher builders are compared by fingerprint (a fixed probe hashed
+    /// through each), so two `RandomState`s with different keys are
+    /// incompatible even though they have the same type.
+    pub fn is_compatible_with(&self, other: &Self) -> bool {
+        self.check_compatible(other).is_ok()
+    }
+
+    /// Strategy used to derive bit positions.
+    pub fn index_strategy(&self) -> IndexStrategy {
+        self.strategy
+    }
+
+    /// Fingerprint of the hasher builder, as compared by `is_compatible_with`.
+    pub fn hasher_fingerprint(&self) -> u32 {
+        self.fingerprint
+    }
+
     /// Clear all bits and reset the item counter to zero.
     pub fn clear(&mut self) {
         self.bits.clear();
@@ -194,11 +557,83 @@ where
         self.items
     }
 
+    /// Number of bits (`m`).
+    pub fn num_bits(&self) -> usize {
+        self.m
+    }
+
+    /// Number of hash functions (`k`).
+    pub fn num_hashes(&self) -> u32 {
+        self.k
+    }
+
+    /// Capacity and false-positive rate the filter was sized for, if known.
+    pub fn design_params(&self) -> Option<(usize, f64)> {
+        if self.design.0 == 0 { None } else { Some(self.design) }
+    }
+
+    /// Whether no bit is set (nothing inserted since creation or `clear`).
+    pub fn is_empty(&self) -> bool {
+        !self.bits.any()
+    }
+
+    /// Number of bits currently set.
+    pub fn count_ones(&self) -> usize {
+        self.bits.count_ones()
+    }
+
+    /// Fraction of bits currently set.
+    pub fn fill_ratio(&self) -> f64 {
+        self.count_ones() as f64 / self.m as f64
+    }
+
+    /// False-positive rate implied by the current fill (`fill_ratio^k`).
+    ///
+    /// Unlike an estimate from `approximate_items`, this accounts for
+    /// duplicate inserts and for bits merged in by `union_inplace`.
+    pub fn current_fpr(&self) -> f64 {
+        self.fill_ratio().powi(self.k as i32)
+    }
+
+    /// Number of distinct items implied by the current fill (`math::estimated_cardinality`).
+    ///
+    /// Infinite once every bit is set.
+    pub fn estimated_cardinality(&self) -> f64 {
+        math::estimated_cardinality(self.m, self.k, self.count_ones())
+    }
+
+    /// Hasher builder used to derive bit positions.
+    pub fn hasher_builder(&self) -> &S {
+        &self.hasher_builder
+    }
+
+    /// Overwrite the item counter (used when restoring from formats that record it).
+    pub(crate) fn set_approximate_items(&mut self, items: usize) {
+        self.items = items;
+    }
+
     /// Serialize the filter into a byte vector.
     ///
     /// Layout:
     ///   words (u64 little‑endian) + m (u64 LE) + k (u32 LE)
+    ///
+    /// The layout predates `IndexStrategy` and single-pass hashing and has
+    /// no room for either; readers assume `DoubleHashing` and the pair
+    /// derivation `with_hasher` picks for the builder. Panics for any other
+    /// filter, which would read back with the wrong bit positions; use
+    /// `to_versioned_bytes` to keep both, or `try_to_bytes` to check first.
     pub fn to_bytes(&self) -> Vec<u8> {
+        self.try_to_bytes().expect(
+            "legacy layout only holds DoubleHashing filters with the builder's default hashing; use to_versioned_bytes",
+        )
+    }
+
+    /// Fallible `to_bytes`: `BloomError::StrategyMismatch` instead of a panic
+    /// for a filter the legacy layout cannot describe.
+    pub fn try_to_bytes(&self) -> Result<Vec<u8>, BloomError> {
+        if self.pair_derivation() != (IndexStrategy::DoubleHashing, hashing::auto_hash128::<S>().is_some()) {
+            return Err(BloomError::StrategyMismatch);
+        }
         let words = self.bits.words_slice();
         let mut out = Vec::with_capacity(words.len() * 8 + 12);
         for w in words {
@@ -206,47 +641,49 @@ where
         }
         out.extend_from_slice(&(self.m as u64).to_le_bytes());
         out.extend_from_slice(&self.k.to_le_bytes());
-        out
+        Ok(out)
     }
 
     /// Deserialize from bytes with an explicit hasher builder.
     ///
     /// Returns `None` if the data length or internal layout is invalid.
     pub fn from_bytes_hasher(data: &[u8], hasher_builder: S) -> Option<Self> {
-        if data.len() < 12 { return None; }
-        let meta_offset = data.len() - 12;
-        let mut m_bytes = [0u8; 8];
-        m_bytes.copy_from_slice(&data[meta_offset..meta_offset+8]);
-        let m = u64::from_le_bytes(m_bytes) as usize;
-
-        let mut k_bytes = [0u8; 4];
-        k_bytes.copy_from_slice(&data[meta_offset+8..meta_offset+12]);
-        let k = u32::from_le_bytes(k_bytes);
-
-        let words_expected = m.div_ceil(64);
-        if meta_offset != words_expected * 8 { return None; }
-
-        let mut words = Vec::with_capacity(words_expected);
-        for i in 0..words_expected {
-            let start = i * 8;
-            let mut wb = [0u8; 8];
-            wb.copy_from_slice(&data[start..start+8]);
-            words.push(u64::from_le_bytes(wb));
-        }
-
-        let mut bitset = BitSet::new(m);
-        bitset.words_mut().copy_from_slice(&words);
+        let (m, k, payload) = format::open_legacy(data)?;
+        let m = usize::try_from(m).ok()?;
+        let words: Vec<u64> = payload
+            .chunks_exact(8)
+            .map(|c| u64::from_le_bytes(c.try_into().unwrap()))
+            .collect();
 
         Some(Self {
-            bits: bitset,
+            bits: BitSet::from_words(m, words),
             m,
             k,
             items: 0,
+            design: (0, 0.0),
+            fingerprint: hashing::hasher_fingerprint(&hasher_builder),
+            strategy: IndexStrategy::default(),
+            hash128: hashing::auto_hash128::<S>(),
             hasher_builder,
             _marker: PhantomData,
         })
     }
 
+    /// Fallible `from_bytes_hasher` for legacy `to_bytes` data, reporting why it was rejected.
+    ///
+    /// For data written by `to_versioned_bytes` use `try_from_versioned_bytes_hasher`.
+    pub fn try_from_bytes_hasher(data: &[u8], hasher_builder: S) -> Result<Self, BloomError> {
+        let (m, k, _) = format::open_legacy(data).ok_or(if data.len() < 12 {
+            BloomError::Truncated
+        } else {
+            BloomError::Corrupt
+        })?;
+        if m == 0 || k == 0 {
+            return Err(BloomError::Corrupt);
+        }
+        Self::from_bytes_hasher(data, hasher_builder).ok_or(BloomError::Corrupt)
+    }
+
     /// Convenience wrapper that rebuilds using a default `RandomState`-derived builder.
     pub fn from_bytes(data: &[u8]) -> Option<Self>
     where
@@ -259,14 +696,197 @@ where
     }
 }
 
+impl<S> BloomFilter<S>
+where
+    S: Hash128Builder + Clone,
+{
+    /// create with a hasher builder that has a 128-bit digest, hashing each item once.
+    ///
+    /// Both probe hashes are split from one `hash_one_128` pass instead of
+    /// the two passes of `hash2`, which roughly halves the hashing cost for
+    /// long keys. `with_hasher` already does this for the crate's own
+    /// builders, so this is only needed for other `Hash128Builder`s; such a
+    /// filter does not merge with a `with_hasher` one of the same builder.
+    /// Versioned files record the choice and reload it through
+    /// `PersistentHasher::digest128`.
+    ///
+    /// **`to_bytes` panics** for such a filter, whose legacy bytes would
+    /// read back two-pass; use `to_versioned_bytes` or `try_to_bytes`.
+    pub fn with_hasher_128(m: usize, k: u32, hasher_builder: S) -> Self {
+        let mut bf = Self::with_hasher(m, k, hasher_builder);
+        bf.hash128 = Some(hashing::hash128_fn::<S>());
+        bf
+    }
+
+    /// `for_capacity_with_hasher` with single-pass hashing (see `with_hasher_128`, also for `to_bytes`).
+    pub fn for_capacity_with_hasher_128(n: usize, p: f64, hasher_builder: S) -> Self {
+        let mut bf = Self::for_capacity_with_hasher(n, p, hasher_builder);
+        bf.hash128 = Some(hashing::hash128_fn::<S>());
+        bf
+    }
+}
+
+impl<S> BloomFilter<S>
+where
+    S: PersistentHasher,
+{
+    /// Serialize into the versioned container (see `format::Header`).
+    ///
+    /// Unlike `to_bytes`, this records the hasher id and seed, the item
+    /// counter and the design parameters, and ends with a CRC32C.
+    pub fn to_versioned_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(HEADER_LEN + self.bits.words_slice().len() * 8 + CHECKSUM_LEN);
+        self.write_to(&mut out).expect("writing to a Vec cannot fail");
+        out
+    }
+
+    /// Stream the versioned container to `w` without materializing it.
+    ///
+    /// Produces the same bytes as `to_versioned_bytes`, converting the words
+    /// through a fixed 64 KiB buffer.
+    pub fn write_to<W: Write>(&self, mut w: W) -> io::Result<()> {
+        let header = self.header().to_bytes();
+        let mut crc = Crc32c::new();
+        crc.update(&header);
+        w.write_all(&header)?;
+        bitset::write_words(&mut w, self.bits.words_slice(), Some(&mut crc))?;
+        w.write_all(&crc.finish().to_le_bytes())
+    }
+
+    /// Read a versioned container from `r`, rebuilding the hasher from the stored seed.
+    ///
+    /// Words are read straight into the filter's storage through a bounded
+    /// buffer. Truncated input fails with `UnexpectedEof`; a bad header,
+    /// hasher mismatch or checksum failure with `InvalidData` wrapping a
+    /// `FormatError`. Legacy `to_bytes` data has no header and is not accepted
+    /// here; use `from_bytes_hasher`. Nothing past the checksum is consumed.
+    pub fn read_from<R: Read>(r: R) -> io::Result<Self> {
+        Self::read_stream(r, None)
+    }
+
+    /// Like `read_from`, with an explicit hasher builder (required for `ID == 0` builders).
+    pub fn read_from_hasher<R: Read>(r: R, hasher_builder: S) -> io::Result<Self> {
+        Self::read_stream(r, Some(hasher_builder))
+    }
+
+    fn read_stream<R: Read>(mut r: R, builder: Option<S>) -> io::Result<Self> {
+        let mut raw = [0u8; HEADER_LEN];
+        r.read_exact(&mut raw)?;
+        let header = format::read_header(&raw).map_err(format::io_error)?;
+        let (hasher_builder, hash128) = format::resolve_hasher(&header, builder).map_err(format::io_error)?;
+        if header.kind != FilterKind::Bloom {
+            return Err(format::io_error(FormatError::WrongKind(header.kind as u8)));
+        }
+        let m = usize::try_from(header.m).map_err(|_| format::io_error(FormatError::InvalidLayout))?;
+        if header.payload_len != m.div_ceil(64) as u64 * 8 {
+            return Err(format::io_error(FormatError::InvalidLayout));
+        }
+
+        let mut crc = Crc32c::new();
+        crc.update(&raw);
+        let words = bitset::read_words(&mut r, m, Some(&mut crc))?;
+        let mut stored = [0u8; CHECKSUM_LEN];
+        r.read_exact(&mut stored)?;
+        if u32::from_le_bytes(stored) != crc.finish() {
+            return Err(format::io_error(FormatError::BadChecksum));
+        }
+
+        let mut bf = Self::from_parts(BitSet::from_words(m, words), header.k, header.items as usize, hasher_builder);
+        bf.design = (header.capacity as usize, header.fpr);
+        bf.strategy = header.strategy;
+        bf.hash128 = hash128;
+        Ok(bf)
+    }
+
+    fn header(&self) -> Header {
+        Header {
+            version: format::VERSION,
+            kind: FilterKind::Bloom,
+            hasher_id: S::ID,
+            seed: self.hasher_builder.seed(),
+            m: self.m as u64,
+            k: self.k,
+            fingerprint: self.fingerprint,
+            strategy: self.strategy,
+            hash128: self.hash128.is_some(),
+            items: self.items as u64,
+            capacity: self.design.0 as u64,
+            fpr: self.design.1,
+            payload_len: self.bits.words_slice().len() as u64 * 8,
+        }
+    }
+
+    /// Deserialize versioned or legacy (`to_bytes`) data, rebuilding the hasher from the stored seed.
+    ///
+    /// Legacy data carries no seed, so it is only accepted for `ID == 0`
+    /// builders (rebuilt with `from_seed`, as `from_bytes` does); use
+    /// `from_versioned_bytes_hasher` for anything else.
+    pub fn from_versioned_bytes(data: &[u8]) -> Result<Self, FormatError> {
+        if !data.starts_with(&format::MAGIC) {
+            if S::ID != 0 {
+                return Err(FormatError::MissingHasher);
+            }
+            return Self::from_bytes_hasher(data, S::from_seed(0)).ok_or(FormatError::InvalidLayout);
+        }
+        let (header, payload) = format::open(data)?;
+        let (hasher_builder, hash128) = format::resolve_hasher(&header, None)?;
+        Self::from_container(&header, payload, hasher_builder, hash128)
+    }
+
+    /// Deserialize versioned or legacy data with an explicit hasher builder.
+    ///
+    /// For versioned data the builder's id (and seed, for seeded builders)
+    /// must match the stored ones, as must its fingerprint, which catches a
+    /// `RandomState` other than the one the filter was built with.
+    pub fn from_versioned_bytes_hasher(data: &[u8], hasher_builder: S) -> Result<Self, FormatError> {
+        if !data.starts_with(&format::MAGIC) {
+            return Self::from_bytes_hasher(data, hasher_builder).ok_or(FormatError::InvalidLayout);
+        }
+        let (header, payload) = format::open(data)?;
+        let (hasher_builder, hash128) = format::resolve_hasher(&header, Some(hasher_builder))?;
+        Self::from_container(&header, payload, hasher_builder, hash128)
+    }
+
+    /// Deserialize versioned or legacy data with an explicit hasher builder, mapping failures to `BloomError`.
+    pub fn try_from_versioned_bytes_hasher(data: &[u8], hasher_builder: S) -> Result<Self, BloomError> {
+        if !data.starts_with(&format::MAGIC) {
+            return Self::try_from_bytes_hasher(data, hasher_builder);
+        }
+        Ok(Self::from_versioned_bytes_hasher(data, hasher_builder)?)
+    }
+
+    fn from_container(
+        header: &Header,
+        payload: &[u8],
+        hasher_builder: S,
+        hash128: Option<Hash128Fn<S>>,
+    ) -> Result<Self, FormatError> {
+        let m = format::bloom_bits(header, payload)?;
+        let words = payload
+            .chunks_exact(8)
+            .map(|c| u64::from_le_bytes(c.try_into().unwrap()))
+            .collect();
+        let mut bf = Self::from_parts(BitSet::from_words(m, words), header.k, header.items as usize, hasher_builder);
+        bf.design = (header.capacity as usize, header.fpr);
+        bf.strategy = header.strategy;
+        bf.hash128 = hash128;
+        Ok(bf)
+    }
+}
+
 #[cfg(feature = "serde")]
 impl<S> serde::Serialize for BloomFilter<S>
-where S: BuildHasher + Clone + Default {
+where S: PersistentHasher {
     fn serialize<Se: Serializer>(&self, serializer: Se) -> Result<Se::Ok, Se::Error> {
-        let mut st = serializer.serialize_struct("BloomFilter", 4)?;
+        let mut st = serializer.serialize_struct("BloomFilter", 9)?;
         st.serialize_field("m", &self.m)?;
         st.serialize_field("k", &self.k)?;
         st.serialize_field("items", &self.items)?;
+        st.serialize_field("hasher", &S::ID)?;
+        st.serialize_field("seed", &self.hasher_builder.seed())?;
+        st.serialize_field("fingerprint", &self.fingerprint)?;
+        st.serialize_field("strategy", &self.strategy.id())?;
+        st.serialize_field("hash128", &self.hash128.is_some())?;
         st.serialize_field("words", self.bits.words_slice())?;
         st.end()
     }
@@ -274,16 +894,52 @@ where S: BuildHasher + Clone + Default {
 
 #[cfg(feature = "serde")]
 impl<'de, S> serde::Deserialize<'de> for BloomFilter<S>
-where S: BuildHasher + Clone + Default {
+where S: PersistentHasher {
     fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
         #[derive(Deserialize)]
-        struct BFHelper { m: usize, k: u32, items: usize, words: Vec<u64> }
+        struct BFHelper {
+            m: usize,
+            k: u32,
+            items: usize,
+            // absent in data written before seeded hashers existed
+            #[serde(default)]
+            hasher: u8,
+            #[serde(default)]
+            seed: u64,
+            #[serde(default)]
+            fingerprint: u32,
+            #[serde(default)]
+            strategy: u8,
+            #[serde(default)]
+            hash128: bool,
+            words: Vec<u64>,
+        }
         let helper = BFHelper::deserialize(deserializer)?;
         let expected = helper.m.div_ceil(64);
         if helper.words.len() != expected {
             return Err(serde::de::Error::custom("words length mismatch"));
         }
+        if helper.k == 0 || helper.k > format::MAX_K {
+            return Err(serde::de::Error::custom("k out of range"));
+        }
+        if helper.hasher != S::ID {
+            return Err(serde::de::Error::custom("hasher id mismatch"));
+        }
+        let hasher_builder = S::from_seed(helper.seed);
+        let fingerprint = hashing::hasher_fingerprint(&hasher_builder);
+        if helper.fingerprint != 0 && helper.fingerprint != fingerprint {
+            return Err(serde::de::Error::custom("hasher fingerprint mismatch"));
+        }
+        let strategy = IndexStrategy::from_id(helper.strategy)
+            .ok_or_else(|| serde::de::Error::custom("unknown index strategy"))?;
+        let hash128: Option<Hash128Fn<S>> = match helper.hash128 {
+            true if hasher_builder.digest128(&0u8).is_none() => {
+                return Err(serde::de::Error::custom("hasher has no 128-bit digest"));
+            }
+            true => Some(|state, item| state.digest128(item).expect("digest checked on deserialization")),
+            false => None,
+        };
         let bitset = BitSet::from_words(helper.m, helper.words);
-        Ok(Self { bits: bitset, m: helper.m, k: helper.k, items: helper.items, hasher_builder: S::default(), _marker: PhantomData })
+        Ok(Self { bits: bitset, m: helper.m, k: helper.k, items: helper.items, design: (0, 0.0), fingerprint, strategy, hash128, hasher_builder, _marker: PhantomData })
     }
 }
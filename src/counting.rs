@@ -0,0 +1,230 @@
+use core::hash::{BuildHasher, Hash};
+
+use std::fmt;
+
+use crate::{bitset::BitSet, bloom::BloomFilter, hashing, math};
+
+/// Width of a single counter in bits.
+const COUNTER_BITS: usize = 4;
+/// Counters packed into each 64-bit word.
+const COUNTERS_PER_WORD: usize = 64 / COUNTER_BITS;
+/// Largest value a counter can hold; reaching it makes the counter sticky.
+pub const COUNTER_MAX: u8 = (1 << COUNTER_BITS) - 1;
+
+/// Error reported when a counter cannot be updated exactly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CountingError {
+    /// At least one counter reached `COUNTER_MAX` and is now saturated.
+    ///
+    /// The operation still took effect, but saturated counters are never
+    /// decremented again, so the affected positions can no longer be freed.
+    Overflow,
+    /// At least one counter would drop below zero, meaning the item was
+    /// never inserted. The filter is left unchanged.
+    Underflow,
+}
+
+impl fmt::Display for CountingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CountingError::Overflow => write!(f, "counter saturated at {}", COUNTER_MAX),
+            CountingError::Underflow => write!(f, "counter underflow: item not present"),
+        }
+    }
+}
+
+impl std::error::Error for CountingError {}
+
+/// Counting bloom filter supporting removal.
+///
+/// Uses the same double-hashing index scheme as `BloomFilter`, but each of
+/// the `m` positions is a 4-bit saturating counter instead of a single bit.
+#[derive(Clone)]
+pub struct CountingBloomFilter<S = std::collections::hash_map::RandomState> {
+    counters: Vec<u64>,
+    m: usize, //number of counters
+    k: u32,   //hash funcs
+    items: usize,
+    hasher_builder: S,
+}
+
+impl<S> fmt::Debug for CountingBloomFilter<S>
+where
+    S: BuildHasher + Clone,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CountingBloomFilter")
+            .field("m(counters)", &self.m)
+            .field("k", &self.k)
+            .field("items", &self.items)
+            .finish()
+    }
+}
+
+impl CountingBloomFilter<std::collections::hash_map::RandomState> {
+    /// convenience constructor using default hasher builder.
+    pub fn new(m: usize, k: u32) -> Self {
+        Self::with_hasher(m, k, std::collections::hash_map::RandomState::new())
+    }
+
+    /// convenience constructor from capacity and false-positive rate with default hasher.
+    pub fn new_for_capacity(n: usize, p: f64) -> Self {
+        let m = math::optimal_m(n, p);
+        let k = math::optimal_k(m, n);
+        Self::with_hasher(m, k, std::collections::hash_map::RandomState::new())
+    }
+}
+
+impl<S> CountingBloomFilter<S>
+where
+    S: BuildHasher + Clone,
+{
+    /// create with explicit hasher builder and `m` counters.
+    pub fn with_hasher(m: usize, k: u32, hasher_builder: S) -> Self {
+        assert!(m > 0 && k > 0);
+        Self {
+            counters: vec![0u64; m.div_ceil(COUNTERS_PER_WORD)],
+            m,
+            k,
+            items: 0,
+            hasher_builder,
+        }
+    }
+
+    fn counter(&self, idx: usize) -> u8 {
+        let shift = (idx % COUNTERS_PER_WORD) * COUNTER_BITS;
+        ((self.counters[idx / COUNTERS_PER_WORD] >> shift) & COUNTER_MAX as u64) as u8
+    }
+
+    fn set_counter(&mut self, idx: usize, value: u8) {
+        let shift = (idx % COUNTERS_PER_WORD) * COUNTER_BITS;
+        let word = &mut self.counters[idx / COUNTERS_PER_WORD];
+        *word = (*word & !((COUNTER_MAX as u64) << shift)) | ((value as u64) << shift);
+    }
+
+    fn indices<T: Hash>(&self, item: &T) -> impl Iterator<Item = usize> {
+        let (h1, h2) = hashing::hash2(&self.hasher_builder, item);
+        let m = self.m;
+        (0..self.k).map(move |i| hashing::nth_index(h1, h2, i, m))
+    }
+
+    /// Insert an item, incrementing its `k` counters.
+    ///
+    /// Returns `Err(CountingError::Overflow)` if any counter saturated; the
+    /// item is still recorded and `contains` will report it.
+    pub fn insert<T: Hash>(&mut self, item: &T) -> Result<(), CountingError> {
+        let mut overflow = false;
+        let indices: Vec<usize> = self.indices(item).collect();
+        for idx in indices {
+            let c = self.counter(idx);
+            if c == COUNTER_MAX {
+                overflow = true;
+            } else {
+                if c + 1 == COUNTER_MAX {
+                    overflow = true;
+                }
+                self.set_counter(idx, c + 1);
+            }
+        }
+        self.items = self.items.saturating_add(1);
+        if overflow { Err(CountingError::Overflow) } else { Ok(()) }
+    }
+
+    /// Remove a previously inserted item, decrementing its `k` counters.
+    ///
+    /// Returns `Err(CountingError::Underflow)` without modifying the filter
+    /// if the item cannot be present. Saturated counters are left untouched.
+    pub fn remove<T: Hash>(&mut self, item: &T) -> Result<(), CountingError> {
+        let mut indices: Vec<usize> = self.indices(item).collect();
+        indices.sort_unstable();
+
+        // A position probed twice by the same item must hold at least two.
+        for run in indices.chunk_by(|a, b| a == b) {
+            let c = self.counter(run[0]);
+            if c != COUNTER_MAX && (c as usize) < run.len() {
+                return Err(CountingError::Underflow);
+            }
+        }
+
+        for idx in indices {
+            let c = self.counter(idx);
+            if c != COUNTER_MAX {
+                self.set_counter(idx, c - 1);
+            }
+        }
+        self.items = self.items.saturating_sub(1);
+        Ok(())
+    }
+
+    /// Test whether an item is *probably* in the set.
+    pub fn contains<T: Hash>(&self, item: &T) -> bool {
+        self.indices(item).all(|idx| self.counter(idx) > 0)
+    }
+
+    /// Upper bound on how many times `item` was inserted.
+    ///
+    /// This is the minimum of its `k` counters, so it is `0` for items that
+    /// are definitely absent and `COUNTER_MAX` once every counter saturated.
+    pub fn count_estimate<T: Hash>(&self, item: &T) -> u8 {
+        self.indices(item).map(|idx| self.counter(idx)).min().unwrap_or(0)
+    }
+
+    /// In‑place union (saturating counter sum) with another filter.
+    ///
+    /// Both filters must have identical `m` and `k` parameters. Returns
+    /// `Err(CountingError::Overflow)` if any counter saturated; the union is
+    /// still applied.
+    pub fn union_inplace(&mut self, other: &Self) -> Result<(), CountingError> {
+        assert_eq!(self.m, other.m, "m mismatch for union");
+        assert_eq!(self.k, other.k, "k mismatch for union");
+        let mut overflow = false;
+        for idx in 0..self.m {
+            let sum = self.counter(idx) + other.counter(idx);
+            if sum >= COUNTER_MAX {
+                overflow = true;
+            }
+            self.set_counter(idx, sum.min(COUNTER_MAX));
+        }
+        self.items = self.items.saturating_add(other.items);
+        if overflow { Err(CountingError::Overflow) } else { Ok(()) }
+    }
+
+    /// Reset all counters and the item counter to zero.
+    pub fn clear(&mut self) {
+        self.counters.fill(0);
+        self.items = 0;
+    }
+
+    /// Net number of successful inserts minus removals.
+    pub fn approximate_items(&self) -> usize {
+        self.items
+    }
+
+    /// Number of counters (`m`).
+    pub fn num_counters(&self) -> usize {
+        self.m
+    }
+
+    /// Number of hash functions (`k`).
+    pub fn num_hashes(&self) -> u32 {
+        self.k
+    }
+
+    /// Number of counters stuck at `COUNTER_MAX`.
+    pub fn saturated_counters(&self) -> usize {
+        (0..self.m).filter(|&idx| self.counter(idx) == COUNTER_MAX).count()
+    }
+
+    /// Collapse into a plain `BloomFilter` with a bit set wherever a counter is non-zero.
+    ///
+    /// The result answers `contains` identically and shares this filter's hasher.
+    pub fn to_bloom_filter(&self) -> BloomFilter<S> {
+        let mut bits = BitSet::new(self.m);
+        for idx in 0..self.m {
+            if self.counter(idx) > 0 {
+                bits.set(idx);
+            }
+        }
+        BloomFilter::from_parts(bits, self.k, self.items, self.hasher_builder.clone())
+    }
+}
@@ -0,0 +1,280 @@
+use core::hash::{BuildHasher, Hash};
+
+use std::fmt;
+
+use crate::math;
+
+/// Default number of fingerprint slots per bucket.
+pub const DEFAULT_BUCKET_SIZE: usize = 4;
+/// Default fingerprint width in bits (~0.2% FPR with 4-slot buckets).
+pub const DEFAULT_FINGERPRINT_BITS: u32 = 12;
+/// Default bound on relocations attempted before an insert gives up.
+pub const DEFAULT_MAX_KICKS: usize = 500;
+
+/// Error returned when an item cannot be stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CuckooError {
+    /// No free slot was found within the kick-out bound.
+    ///
+    /// The filter is left exactly as it was before the call.
+    Full,
+}
+
+impl fmt::Display for CuckooError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CuckooError::Full => write!(f, "cuckoo filter is full"),
+        }
+    }
+}
+
+impl std::error::Error for CuckooError {}
+
+/// Cuckoo filter (Fan et al., 2014) with deletion support.
+///
+/// Stores an `f`-bit fingerprint of each item in one of two candidate
+/// buckets of `b` slots. Fingerprints are bit-packed, so at low false-positive
+/// rates (roughly below 0.5%) this uses less space than a `BloomFilter` with
+/// the same error.
+#[derive(Clone)]
+pub struct CuckooFilter<S = std::collections::hash_map::RandomState> {
+    slots: Vec<u64>,
+    num_buckets: usize,
+    bucket_size: usize,
+    fp_bits: u32,
+    max_kicks: usize,
+    len: usize,
+    rng: u64,
+    hasher_builder: S,
+}
+
+impl<S> fmt::Debug for CuckooFilter<S>
+where
+    S: BuildHasher + Clone,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CuckooFilter")
+            .field("buckets", &self.num_buckets)
+            .field("bucket_size", &self.bucket_size)
+            .field("fingerprint_bits", &self.fp_bits)
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+impl CuckooFilter<std::collections::hash_map::RandomState> {
+    /// convenience constructor with default fingerprint and bucket sizes.
+    pub fn new(capacity: usize) -> Self {
+        Self::with_hasher(capacity, DEFAULT_FINGERPRINT_BITS, DEFAULT_BUCKET_SIZE, std::collections::hash_map::RandomState::new())
+    }
+
+    /// convenience constructor picking the fingerprint width for false-positive rate `p`.
+    pub fn new_for_capacity(capacity: usize, p: f64) -> Self {
+        let fp_bits = math::cuckoo_fingerprint_bits(p, DEFAULT_BUCKET_SIZE);
+        Self::with_hasher(capacity, fp_bits, DEFAULT_BUCKET_SIZE, std::collections::hash_map::RandomState::new())
+    }
+}
+
+impl<S> CuckooFilter<S>
+where
+    S: BuildHasher + Clone,
+{
+    /// create with explicit fingerprint width (2..=32 bits), bucket size and hasher builder.
+    ///
+    /// The bucket count is chosen so `capacity` items fill ~95% of the slots.
+    pub fn with_hasher(capacity: usize, fingerprint_bits: u32, bucket_size: usize, hasher_builder: S) -> Self {
+        assert!(capacity > 0, "capacity must be > 0");
+        assert!((2..=32).contains(&fingerprint_bits), "fingerprint_bits must be in 2..=32");
+        assert!(bucket_size > 0, "bucket_size must be > 0");
+        let num_buckets = ((capacity as f64 / (bucket_size as f64 * 0.95)).ceil() as usize).max(2);
+        let total_bits = num_buckets * bucket_size * fingerprint_bits as usize;
+        Self {
+            slots: vec![0u64; total_bits.div_ceil(64)],
+            num_buckets,
+            bucket_size,
+            fp_bits: fingerprint_bits,
+            max_kicks: DEFAULT_MAX_KICKS,
+            len: 0,
+            rng: 0x9e37_79b9_7f4a_7c15,
+            hasher_builder,
+        }
+    }
+
+    /// Set the bound on relocations attempted by a single insert.
+    pub fn set_max_kicks(&mut self, max_kicks: usize) {
+        self.max_kicks = max_kicks;
+    }
+
+    fn slot(&self, bucket: usize, j: usize) -> u32 {
+        let off = (bucket * self.bucket_size + j) * self.fp_bits as usize;
+        let (w, shift) = (off / 64, off % 64);
+        let mut v = self.slots[w] >> shift;
+        if shift + self.fp_bits as usize > 64 {
+            v |= self.slots[w + 1] << (64 - shift);
+        }
+        (v & self.fp_mask()) as u32
+    }
+
+    fn set_slot(&mut self, bucket: usize, j: usize, fp: u32) {
+        let off = (bucket * self.bucket_size + j) * self.fp_bits as usize;
+        let (w, shift) = (off / 64, off % 64);
+        let mask = self.fp_mask();
+        let fp = fp as u64;
+        self.slots[w] = (self.slots[w] & !(mask << shift)) | (fp << shift);
+        if shift + self.fp_bits as usize > 64 {
+            let spill = 64 - shift;
+            self.slots[w + 1] = (self.slots[w + 1] & !(mask >> spill)) | (fp >> spill);
+        }
+    }
+
+    fn fp_mask(&self) -> u64 {
+        (1u64 << self.fp_bits) - 1
+    }
+
+    /// Fingerprint (never zero, zero marks an empty slot) and primary bucket.
+    fn fingerprint_and_index<T: Hash>(&self, item: &T) -> (u32, usize) {
+        let h = self.hasher_builder.hash_one(item);
+        let mut fp = (h & self.fp_mask()) as u32;
+        if fp == 0 {
+            fp = 1;
+        }
+        let index = (h.rotate_right(32) % self.num_buckets as u64) as usize;
+        (fp, index)
+    }
+
+    /// The other candidate bucket for `fp`; applying it twice returns `index`.
+    ///
+    /// Uses `(hash(fp) - index) mod n` rather than the usual xor so the
+    /// bucket count need not be a power of two.
+    fn alt_index(&self, index: usize, fp: u32) -> usize {
+        let n = self.num_buckets as u64;
+        let hfp = (fp as u64).wrapping_mul(0xc6a4_a793_5bd1_e995) % n;
+        ((hfp + n - index as u64) % n) as usize
+    }
+
+    fn find_in(&self, bucket: usize, fp: u32) -> Option<usize> {
+        (0..self.bucket_size).find(|&j| self.slot(bucket, j) == fp)
+    }
+
+    fn try_place(&mut self, bucket: usize, fp: u32) -> bool {
+        match self.find_in(bucket, 0) {
+            Some(j) => {
+                self.set_slot(bucket, j, fp);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn next_random(&mut self) -> u64 {
+        // xorshift64*
+        self.rng ^= self.rng >> 12;
+        self.rng ^= self.rng << 25;
+        self.rng ^= self.rng >> 27;
+        self.rng.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// Insert an item.
+    ///
+    /// Inserting the same item twice stores two copies (at most `2 * b`).
+    /// Returns `Err(CuckooError::Full)` if no slot frees up within the
+    /// kick-out bound; any relocations made during the attempt are undone.
+    pub fn insert<T: Hash>(&mut self, item: &T) -> Result<(), CuckooError> {
+        let (fp, i1) = self.fingerprint_and_index(item);
+        let i2 = self.alt_index(i1, fp);
+        if self.try_place(i1, fp) || self.try_place(i2, fp) {
+            self.len += 1;
+            return Ok(());
+        }
+
+        let mut bucket = if self.next_random() & 1 == 0 { i1 } else { i2 };
+        let mut fp = fp;
+        let mut swaps: Vec<(usize, usize, u32)> = Vec::new();
+        for _ in 0..self.max_kicks {
+            let j = (self.next_random() % self.bucket_size as u64) as usize;
+            let victim = self.slot(bucket, j);
+            self.set_slot(bucket, j, fp);
+            swaps.push((bucket, j, victim));
+            fp = victim;
+            bucket = self.alt_index(bucket, fp);
+            if self.try_place(bucket, fp) {
+                self.len += 1;
+                return Ok(());
+            }
+        }
+
+        for (bucket, j, victim) in swaps.into_iter().rev() {
+            self.set_slot(bucket, j, victim);
+        }
+        Err(CuckooError::Full)
+    }
+
+    /// Test whether an item is *probably* in the set.
+    pub fn contains<T: Hash>(&self, item: &T) -> bool {
+        let (fp, i1) = self.fingerprint_and_index(item);
+        self.find_in(i1, fp).is_some() || self.find_in(self.alt_index(i1, fp), fp).is_some()
+    }
+
+    /// Remove one copy of an item. Returns `true` if a matching fingerprint was found.
+    ///
+    /// Only remove items that were actually inserted: removing a false
+    /// positive deletes another item's fingerprint.
+    pub fn remove<T: Hash>(&mut self, item: &T) -> bool {
+        let (fp, i1) = self.fingerprint_and_index(item);
+        for bucket in [i1, self.alt_index(i1, fp)] {
+            if let Some(j) = self.find_in(bucket, fp) {
+                self.set_slot(bucket, j, 0);
+                self.len -= 1;
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Remove all items.
+    pub fn clear(&mut self) {
+        self.slots.fill(0);
+        self.len = 0;
+    }
+
+    /// Number of stored fingerprints.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// `true` if no fingerprints are stored.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Total number of fingerprint slots.
+    pub fn capacity(&self) -> usize {
+        self.num_buckets * self.bucket_size
+    }
+
+    /// Fraction of slots in use.
+    pub fn load_factor(&self) -> f64 {
+        self.len as f64 / self.capacity() as f64
+    }
+
+    /// Fingerprint width in bits.
+    pub fn fingerprint_bits(&self) -> u32 {
+        self.fp_bits
+    }
+
+    /// Slots per bucket.
+    pub fn bucket_size(&self) -> usize {
+        self.bucket_size
+    }
+
+    /// Upper bound on the false-positive rate at the current load: `2b / 2^f`.
+    pub fn estimated_fpr(&self) -> f64 {
+        let upper = 2.0 * self.bucket_size as f64 / (1u64 << self.fp_bits) as f64;
+        (upper * self.load_factor()).min(1.0)
+    }
+
+    /// Size of the fingerprint table in bytes.
+    pub fn size_in_bytes(&self) -> usize {
+        self.slots.len() * 8
+    }
+}
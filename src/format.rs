@@ -0,0 +1,359 @@
+use std::fmt;
+use std::io;
+
+use crate::hashing::{self, Hash128Fn};
+use crate::index::IndexStrategy;
+use crate::seeded::PersistentHasher;
+
+/// Magic bytes opening every versioned file.
+pub const MAGIC: [u8; 4] = *b"BLMZ";
+/// Newest format version this crate writes.
+pub const VERSION: u16 = 1;
+/// Size of the fixed header in bytes.
+pub const HEADER_LEN: usize = 64;
+/// Size of the CRC32C trailer in bytes.
+pub const CHECKSUM_LEN: usize = 4;
+/// Largest `k` the header can hold (24 bits); filters refuse larger ones at construction.
+pub const MAX_K: u32 = (1 << 24) - 1;
+/// Bit of the strategy byte set when probe pairs come from a 128-bit digest.
+const HASH128_FLAG: u8 = 0x80;
+/// Byte offset of the item counter inside the header.
+pub(crate) const ITEMS_OFFSET: usize = 32;
+
+/// Error returned when versioned data cannot be read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormatError {
+    /// The data does not start with `MAGIC`.
+    BadMagic,
+    /// The data was written by a newer format version.
+    UnsupportedVersion(u16),
+    /// The file holds a different filter kind.
+    WrongKind(u8),
+    /// The file was written with a different hasher (ids shown as stored / expected).
+    HasherMismatch {
+        /// Hasher id in the file.
+        found: u8,
+        /// Hasher id of the requested builder.
+        expected: u8,
+    },
+    /// The file uses an `IndexStrategy` id this version does not know.
+    UnknownStrategy(u8),
+    /// The builder has the right id but hashes differently (e.g. another `RandomState` key).
+    FingerprintMismatch,
+    /// Legacy data carries no seed; use the `_hasher` variant with the original builder.
+    MissingHasher,
+    /// The data ends before the length the header announces.
+    Truncated,
+    /// The CRC32C over header and payload does not match.
+    BadChecksum,
+    /// Header fields are inconsistent (e.g. payload size does not match `m`).
+    InvalidLayout,
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FormatError::BadMagic => write!(f, "not a bloomz file (bad magic)"),
+            FormatError::UnsupportedVersion(v) => write!(f, "unsupported format version {}", v),
+            FormatError::WrongKind(k) => write!(f, "unexpected filter kind {}", k),
+            FormatError::HasherMismatch { found, expected } => {
+                write!(f, "hasher mismatch: file has id {}, expected {}", found, expected)
+            }
+            FormatError::UnknownStrategy(id) => write!(f, "unknown index strategy {}", id),
+            FormatError::FingerprintMismatch => write!(f, "hasher fingerprint mismatch"),
+            FormatError::MissingHasher => write!(f, "legacy data needs an explicit hasher builder"),
+            FormatError::Truncated => write!(f, "data truncated"),
+            FormatError::BadChecksum => write!(f, "checksum mismatch"),
+            FormatError::InvalidLayout => write!(f, "invalid header layout"),
+        }
+    }
+}
+
+impl std::error::Error for FormatError {}
+
+/// Which filter a versioned file holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterKind {
+    /// `BloomFilter`: payload is the bit words, little-endian.
+    Bloom = 1,
+}
+
+impl FilterKind {
+    fn from_u8(v: u8) -> Option<Self> {
+        match v {
+            1 => Some(FilterKind::Bloom),
+            _ => None,
+        }
+    }
+}
+
+/// Fixed-size header of a versioned file.
+///
+/// Layout (all little-endian):
+///   magic [4] + version u16 + kind u8 + hasher id u8 + seed u64 + m u64
+///   + k u24 + index strategy u8 + hasher fingerprint u32 + items u64
+///   + design capacity u64 + design fpr f64 + payload length u64
+///
+/// followed by the payload and a CRC32C of header and payload (u32 LE).
+/// The top bit of the strategy byte is the `hash128` flag.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Header {
+    /// Format version the file was written with.
+    pub version: u16,
+    /// Filter stored in the payload.
+    pub kind: FilterKind,
+    /// `PersistentHasher::ID` of the builder.
+    pub hasher_id: u8,
+    /// `PersistentHasher::seed` of the builder.
+    pub seed: u64,
+    /// Number of bits.
+    pub m: u64,
+    /// Number of hash functions (below `2^24`).
+    pub k: u32,
+    /// How bit positions are derived.
+    pub strategy: IndexStrategy,
+    /// Whether probe pairs come from the builder's 128-bit digest (see `BloomFilter::with_hasher_128`).
+    pub hash128: bool,
+    /// Fingerprint of the hasher builder.
+    pub fingerprint: u32,
+    /// Item counter at the time of writing.
+    pub items: u64,
+    /// Capacity the filter was sized for, `0` if unknown.
+    pub capacity: u64,
+    /// False-positive rate the filter was sized for, `0.0` if unknown.
+    pub fpr: f64,
+    /// Payload size in bytes.
+    pub payload_len: u64,
+}
+
+impl Header {
+    /// Encode into the fixed `HEADER_LEN` byte layout.
+    pub fn to_bytes(&self) -> [u8; HEADER_LEN] {
+        let mut out = [0u8; HEADER_LEN];
+        out[0..4].copy_from_slice(&MAGIC);
+        out[4..6].copy_from_slice(&self.version.to_le_bytes());
+        out[6] = self.kind as u8;
+        out[7] = self.hasher_id;
+        out[8..16].copy_from_slice(&self.seed.to_le_bytes());
+        out[16..24].copy_from_slice(&self.m.to_le_bytes());
+        assert!(self.k <= MAX_K, "k too large for the versioned format");
+        let strategy = self.strategy.id() | if self.hash128 { HASH128_FLAG } else { 0 };
+        out[24..28].copy_from_slice(&(self.k | (strategy as u32) << 24).to_le_bytes());
+        out[28..32].copy_from_slice(&self.fingerprint.to_le_bytes());
+        out[ITEMS_OFFSET..ITEMS_OFFSET + 8].copy_from_slice(&self.items.to_le_bytes());
+        out[40..48].copy_from_slice(&self.capacity.to_le_bytes());
+        out[48..56].copy_from_slice(&self.fpr.to_le_bytes());
+        out[56..64].copy_from_slice(&self.payload_len.to_le_bytes());
+        out
+    }
+
+    /// Total encoded size: header, payload and checksum.
+    ///
+    /// `None` if a corrupt `payload_len` makes the sum overflow.
+    pub fn total_len(&self) -> Option<u64> {
+        (HEADER_LEN as u64 + CHECKSUM_LEN as u64).checked_add(self.payload_len)
+    }
+}
+
+/// Parse the header at the start of `data` without touching the payload.
+///
+/// Only the first `HEADER_LEN` bytes are read, so this is cheap even for
+/// huge files: read that many bytes from disk and pass them in. The checksum
+/// is *not* verified here.
+pub fn read_header(data: &[u8]) -> Result<Header, FormatError> {
+    if data.len() < 4 || data[0..4] != MAGIC {
+        return Err(FormatError::BadMagic);
+    }
+    if data.len() < HEADER_LEN {
+        return Err(FormatError::Truncated);
+    }
+    let u64_at = |at: usize| u64::from_le_bytes(data[at..at + 8].try_into().unwrap());
+    let version = u16::from_le_bytes([data[4], data[5]]);
+    if version == 0 || version > VERSION {
+        return Err(FormatError::UnsupportedVersion(version));
+    }
+    let kind = FilterKind::from_u8(data[6]).ok_or(FormatError::WrongKind(data[6]))?;
+    let id = data[27] & !HASH128_FLAG;
+    let strategy = IndexStrategy::from_id(id).ok_or(FormatError::UnknownStrategy(id))?;
+    let header = Header {
+        version,
+        kind,
+        hasher_id: data[7],
+        seed: u64_at(8),
+        m: u64_at(16),
+        k: u32::from_le_bytes([data[24], data[25], data[26], 0]),
+        strategy,
+        hash128: data[27] & HASH128_FLAG != 0,
+        fingerprint: u32::from_le_bytes(data[28..32].try_into().unwrap()),
+        items: u64_at(32),
+        capacity: u64_at(40),
+        fpr: f64::from_le_bytes(data[48..56].try_into().unwrap()),
+        payload_len: u64_at(56),
+    };
+    if header.m == 0 || header.k == 0 {
+        return Err(FormatError::InvalidLayout);
+    }
+    Ok(header)
+}
+
+/// Split a complete versioned file into header and payload, verifying length and checksum.
+pub(crate) fn open(data: &[u8]) -> Result<(Header, &[u8]), FormatError> {
+    let (header, payload) = open_unchecked(data)?;
+    verify_checksum(data)?;
+    Ok((header, payload))
+}
+
+/// Like `open`, but skips the checksum (O(1) for callers that defer it).
+pub(crate) fn open_unchecked(data: &[u8]) -> Result<(Header, &[u8]), FormatError> {
+    let header = read_header(data)?;
+    let total_len = header.total_len().ok_or(FormatError::InvalidLayout)?;
+    if (data.len() as u64) < total_len {
+        return Err(FormatError::Truncated);
+    }
+    if data.len() as u64 != total_len {
+        return Err(FormatError::InvalidLayout);
+    }
+    Ok((header, &data[HEADER_LEN..data.len() - CHECKSUM_LEN]))
+}
+
+/// Compare the stored trailer of a complete versioned file with its CRC32C.
+pub(crate) fn verify_checksum(data: &[u8]) -> Result<(), FormatError> {
+    let body = data.len() - CHECKSUM_LEN;
+    let stored = u32::from_le_bytes(data[body..].try_into().unwrap());
+    if crc32c(&data[..body]) != stored {
+        return Err(FormatError::BadChecksum);
+    }
+    Ok(())
+}
+
+/// Split legacy `to_bytes` data (words + m u64 + k u32) into `m`, `k` and payload.
+pub(crate) fn open_legacy(data: &[u8]) -> Option<(u64, u32, &[u8])> {
+    let meta = data.len().checked_sub(12)?;
+    let m = u64::from_le_bytes(data[meta..meta + 8].try_into().unwrap());
+    let k = u32::from_le_bytes(data[meta + 8..].try_into().unwrap());
+    if m.div_ceil(64).checked_mul(8) != Some(meta as u64) || k > MAX_K {
+        return None;
+    }
+    Some((m, k, &data[..meta]))
+}
+
+/// Hasher builder picked by `resolve_hasher`, with the digest a single-pass file needs.
+pub(crate) type Resolved<S> = (S, Option<Hash128Fn<S>>);
+
+/// Pick the hasher builder for a header: `builder` if given, else one rebuilt
+/// from the stored seed, along with its 128-bit digest if the file was
+/// written with single-pass hashing. Rejects a different builder type, a
+/// different seed and a different fingerprint.
+pub(crate) fn resolve_hasher<S: PersistentHasher>(header: &Header, builder: Option<S>) -> Result<Resolved<S>, FormatError> {
+    let mismatch = FormatError::HasherMismatch { found: header.hasher_id, expected: S::ID };
+    if header.hasher_id != S::ID {
+        return Err(mismatch);
+    }
+    let builder = match builder {
+        Some(b) if S::ID != 0 && b.seed() != header.seed => return Err(mismatch),
+        Some(b) => b,
+        None => S::from_seed(header.seed),
+    };
+    if header.fingerprint != hashing::hasher_fingerprint(&builder) {
+        return Err(FormatError::FingerprintMismatch);
+    }
+    if !header.hash128 {
+        return Ok((builder, None));
+    }
+    // positions were split from a 128-bit digest this builder cannot produce
+    if builder.digest128(&0u8).is_none() {
+        return Err(FormatError::FingerprintMismatch);
+    }
+    let digest: Hash128Fn<S> = |state, item| state.digest128(item).expect("digest checked when the filter was read");
+    Ok((builder, Some(digest)))
+}
+
+/// Validate a `FilterKind::Bloom` payload and return its bit count.
+pub(crate) fn bloom_bits(header: &Header, payload: &[u8]) -> Result<usize, FormatError> {
+    if header.kind != FilterKind::Bloom {
+        return Err(FormatError::WrongKind(header.kind as u8));
+    }
+    let m = usize::try_from(header.m).map_err(|_| FormatError::InvalidLayout)?;
+    if payload.len() != m.div_ceil(64) * 8 {
+        return Err(FormatError::InvalidLayout);
+    }
+    Ok(m)
+}
+
+/// Convert a format error into the `io::Error` reported by streaming and mmap readers.
+pub(crate) fn io_error(e: FormatError) -> io::Error {
+    let kind = match e {
+        FormatError::Truncated => io::ErrorKind::UnexpectedEof,
+        _ => io::ErrorKind::InvalidData,
+    };
+    io::Error::new(kind, e)
+}
+
+/// CRC32C (Castagnoli) lookup tables for slicing-by-8.
+const CRC_TABLES: [[u32; 256]; 8] = {
+    let mut tables = [[0u32; 256]; 8];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ 0x82f6_3b78 } else { crc >> 1 };
+            bit += 1;
+        }
+        tables[0][i] = crc;
+        i += 1;
+    }
+    let mut t = 1;
+    while t < 8 {
+        let mut i = 0;
+        while i < 256 {
+            let prev = tables[t - 1][i];
+            tables[t][i] = (prev >> 8) ^ tables[0][(prev & 0xff) as usize];
+            i += 1;
+        }
+        t += 1;
+    }
+    tables
+};
+
+/// Incremental CRC32C.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Crc32c(u32);
+
+impl Crc32c {
+    pub(crate) fn new() -> Self {
+        Crc32c(!0)
+    }
+
+    pub(crate) fn update(&mut self, data: &[u8]) {
+        let t = &CRC_TABLES;
+        let mut crc = self.0;
+        let mut chunks = data.chunks_exact(8);
+        for c in &mut chunks {
+            let lo = crc ^ u32::from_le_bytes([c[0], c[1], c[2], c[3]]);
+            crc = t[7][(lo & 0xff) as usize]
+                ^ t[6][((lo >> 8) & 0xff) as usize]
+                ^ t[5][((lo >> 16) & 0xff) as usize]
+                ^ t[4][(lo >> 24) as usize]
+                ^ t[3][c[4] as usize]
+                ^ t[2][c[5] as usize]
+                ^ t[1][c[6] as usize]
+                ^ t[0][c[7] as usize];
+        }
+        for &b in chunks.remainder() {
+            crc = (crc >> 8) ^ t[0][((crc ^ b as u32) & 0xff) as usize];
+        }
+        self.0 = crc;
+    }
+
+    pub(crate) fn finish(&self) -> u32 {
+        !self.0
+    }
+}
+
+/// CRC32C of `data` in one call.
+pub fn crc32c(data: &[u8]) -> u32 {
+    let mut crc = Crc32c::new();
+    crc.update(data);
+    crc.finish()
+}
@@ -0,0 +1,375 @@
+use core::hash::{BuildHasher, Hash};
+use core::ops::BitXor;
+
+use std::fmt;
+
+use crate::hashing::{murmur64, splitmix64};
+
+/// Construction attempts (each with a fresh seed) before giving up.
+const MAX_ITERATIONS: usize = 100;
+/// Number of array positions each key maps to.
+const ARITY: usize = 3;
+
+/// Error returned when a binary fuse filter cannot be built.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FuseError {
+    /// The key hypergraph could not be peeled within the retry bound.
+    ///
+    /// This is astronomically unlikely for distinct keys; in practice it
+    /// means the hasher maps many different keys to the same 64-bit value.
+    PeelFailed,
+}
+
+impl fmt::Display for FuseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FuseError::PeelFailed => write!(f, "failed to peel binary fuse graph after {} attempts", MAX_ITERATIONS),
+        }
+    }
+}
+
+impl std::error::Error for FuseError {}
+
+mod sealed {
+    pub trait Sealed {}
+    impl Sealed for u8 {}
+    impl Sealed for u16 {}
+}
+
+/// Fingerprint types a `BinaryFuseFilter` can store (`u8` or `u16`).
+pub trait FuseFingerprint: sealed::Sealed + Copy + Default + Eq + BitXor<Output = Self> {
+    /// Width in bits.
+    const BITS: u32;
+    /// Truncate a 64-bit hash to a fingerprint.
+    fn from_hash(hash: u64) -> Self;
+    /// Append the little-endian encoding to `out`.
+    fn write_le(self, out: &mut Vec<u8>);
+    /// Decode from exactly `BITS / 8` little-endian bytes.
+    fn read_le(bytes: &[u8]) -> Self;
+}
+
+impl FuseFingerprint for u8 {
+    const BITS: u32 = 8;
+    fn from_hash(hash: u64) -> Self { (hash ^ (hash >> 32)) as u8 }
+    fn write_le(self, out: &mut Vec<u8>) { out.push(self) }
+    fn read_le(bytes: &[u8]) -> Self { bytes[0] }
+}
+
+impl FuseFingerprint for u16 {
+    const BITS: u32 = 16;
+    fn from_hash(hash: u64) -> Self { (hash ^ (hash >> 32)) as u16 }
+    fn write_le(self, out: &mut Vec<u8>) { out.extend_from_slice(&self.to_le_bytes()) }
+    fn read_le(bytes: &[u8]) -> Self { u16::from_le_bytes([bytes[0], bytes[1]]) }
+}
+
+/// Static binary fuse filter (Graf & Lemire, 2022).
+///
+/// Built once from a fixed key set and then only queried. Each key maps to
+/// three fingerprint slots in neighbouring segments whose XOR equals the
+/// key's fingerprint. With 8-bit fingerprints this needs about 9 bits per
+/// key for a ~0.4% false-positive rate; 16-bit fingerprints give ~0.0015%.
+#[derive(Clone)]
+pub struct BinaryFuseFilter<F = u8, S = std::collections::hash_map::RandomState> {
+    fingerprints: Vec<F>,
+    seed: u64,
+    segment_length: u32,
+    segment_count_length: u32,
+    len: usize,
+    hasher_builder: S,
+}
+
+/// Binary fuse filter with 8-bit fingerprints.
+pub type BinaryFuse8<S = std::collections::hash_map::RandomState> = BinaryFuseFilter<u8, S>;
+/// Binary fuse filter with 16-bit fingerprints.
+pub type BinaryFuse16<S = std::collections::hash_map::RandomState> = BinaryFuseFilter<u16, S>;
+
+impl<F, S> fmt::Debug for BinaryFuseFilter<F, S>
+where
+    F: FuseFingerprint,
+    S: BuildHasher + Clone,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BinaryFuseFilter")
+            .field("fingerprint_bits", &F::BITS)
+            .field("slots", &self.fingerprints.len())
+            .field("segment_length", &self.segment_length)
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+impl<F: FuseFingerprint> BinaryFuseFilter<F, std::collections::hash_map::RandomState> {
+    /// convenience constructor using default hasher builder.
+    pub fn from_keys<T: Hash>(keys: &[T]) -> Result<Self, FuseError> {
+        Self::from_keys_with_hasher(keys, std::collections::hash_map::RandomState::new())
+    }
+}
+
+/// Segment geometry for `size` keys: (segment_length, segment_count, array_length).
+fn layout(size: usize) -> (u32, u32, usize) {
+    let segment_length: u32 = if size == 0 {
+        4
+    } else {
+        let exp = ((size as f64).ln() / 3.33f64.ln() + 2.25).floor() as u32;
+        (1u32 << exp.min(18)).min(262_144)
+    };
+    let size_factor = if size <= 1 {
+        0.0
+    } else {
+        (0.875 + 0.25 * 1_000_000f64.ln() / (size as f64).ln()).max(1.125)
+    };
+    let capacity = (size as f64 * size_factor).round() as usize;
+    let seg = segment_length as usize;
+    let init_segment_count = capacity.div_ceil(seg).saturating_sub(ARITY - 1);
+    let mut segment_count = ((init_segment_count + ARITY - 1) * seg).div_ceil(seg);
+    if segment_count < ARITY {
+        segment_count = 1;
+    } else {
+        segment_count -= ARITY - 1;
+    }
+    let array_length = (segment_count + ARITY - 1) * seg;
+    (segment_length, segment_count as u32, array_length)
+}
+
+impl<F, S> BinaryFuseFilter<F, S>
+where
+    F: FuseFingerprint,
+    S: BuildHasher + Clone,
+{
+    /// Build from a key slice with an explicit hasher builder.
+    ///
+    /// Keys that hash to the same 64-bit value are treated as duplicates.
+    /// Returns `Err(FuseError::PeelFailed)` if no seed yields a peelable graph.
+    pub fn from_keys_with_hasher<T: Hash>(keys: &[T], hasher_builder: S) -> Result<Self, FuseError> {
+        let size = keys.len();
+        let (segment_length, segment_count, array_length) = layout(size);
+        let mut filter = Self {
+            fingerprints: vec![F::default(); array_length],
+            seed: 0,
+            segment_length,
+            segment_count_length: segment_count * segment_length,
+            len: size,
+            hasher_builder,
+        };
+        let key_hashes: Vec<u64> = keys.iter().map(|k| filter.hasher_builder.hash_one(k)).collect();
+        filter.populate(&key_hashes, segment_count)?;
+        Ok(filter)
+    }
+
+    /// The three slots of a mixed hash, one per consecutive segment.
+    #[inline]
+    fn positions(&self, hash: u64) -> [usize; ARITY] {
+        let mask = (self.segment_length - 1) as u64;
+        let h0 = ((hash as u128 * self.segment_count_length as u128) >> 64) as u64;
+        let h1 = (h0 + self.segment_length as u64) ^ ((hash >> 18) & mask);
+        let h2 = (h0 + 2 * self.segment_length as u64) ^ (hash & mask);
+        [h0 as usize, h1 as usize, h2 as usize]
+    }
+
+    fn populate(&mut self, key_hashes: &[u64], segment_count: u32) -> Result<(), FuseError> {
+        let size = key_hashes.len();
+        let capacity = self.fingerprints.len();
+        let mut rng = 0x726b_2b9d_438b_9d4du64;
+        self.seed = splitmix64(&mut rng);
+
+        let mut reverse_order = vec![0u64; size + 1];
+        reverse_order[size] = 1; // sentinel
+        let mut reverse_h = vec![0u8; size];
+        let mut alone = vec![0usize; capacity];
+        let mut t2count = vec![0u8; capacity];
+        let mut t2hash = vec![0u64; capacity];
+
+        let mut block_bits = 1u32;
+        while (1u32 << block_bits) < segment_count {
+            block_bits += 1;
+        }
+        let block = 1usize << block_bits;
+        let mut start_pos = vec![0usize; block];
+
+        let mut stack_size;
+        let mut iteration = 0usize;
+        loop {
+            iteration += 1;
+            if iteration > MAX_ITERATIONS {
+                return Err(FuseError::PeelFailed);
+            }
+
+            // bucket the mixed hashes by their top bits so positions are
+            // visited roughly in array order (much better cache behaviour)
+            for (i, pos) in start_pos.iter_mut().enumerate() {
+                *pos = (i * size) >> block_bits;
+            }
+            for &key in key_hashes {
+                let hash = murmur64(key.wrapping_add(self.seed));
+                let mut segment = (hash >> (64 - block_bits)) as usize;
+                while reverse_order[start_pos[segment]] != 0 {
+                    segment = (segment + 1) & (block - 1);
+                }
+                reverse_order[start_pos[segment]] = hash;
+                start_pos[segment] += 1;
+            }
+
+            // count degrees; the low two bits of t2count xor the slot ids
+            let mut error = false;
+            let mut duplicates = 0usize;
+            for &hash in &reverse_order[..size] {
+                let [h0, h1, h2] = self.positions(hash);
+                t2count[h0] = t2count[h0].wrapping_add(4);
+                t2hash[h0] ^= hash;
+                t2count[h1] = t2count[h1].wrapping_add(4) ^ 1;
+                t2hash[h1] ^= hash;
+                t2count[h2] = t2count[h2].wrapping_add(4) ^ 2;
+                t2hash[h2] ^= hash;
+
+                // a key seen twice cancels itself out of t2hash
+                if t2hash[h0] & t2hash[h1] & t2hash[h2] == 0
+                    && ((t2hash[h0] == 0 && t2count[h0] == 8)
+                        || (t2hash[h1] == 0 && t2count[h1] == 8)
+                        || (t2hash[h2] == 0 && t2count[h2] == 8))
+                {
+                    duplicates += 1;
+                    t2count[h0] = t2count[h0].wrapping_sub(4);
+                    t2hash[h0] ^= hash;
+                    t2count[h1] = t2count[h1].wrapping_sub(4) ^ 1;
+                    t2hash[h1] ^= hash;
+                    t2count[h2] = t2count[h2].wrapping_sub(4) ^ 2;
+                    t2hash[h2] ^= hash;
+                }
+                // degree counter overflowed
+                error |= t2count[h0] < 4 || t2count[h1] < 4 || t2count[h2] < 4;
+            }
+
+            if !error {
+                // peel slots of degree one until none are left
+                let mut qsize = 0usize;
+                for (i, &count) in t2count.iter().enumerate() {
+                    alone[qsize] = i;
+                    if count >> 2 == 1 {
+                        qsize += 1;
+                    }
+                }
+                stack_size = 0;
+                while qsize > 0 {
+                    qsize -= 1;
+                    let index = alone[qsize];
+                    if t2count[index] >> 2 != 1 {
+                        continue;
+                    }
+                    let hash = t2hash[index];
+                    let found = (t2count[index] & 3) as usize;
+                    reverse_h[stack_size] = found as u8;
+                    reverse_order[stack_size] = hash;
+                    stack_size += 1;
+
+                    let pos = self.positions(hash);
+                    for step in 1..ARITY {
+                        let slot = (found + step) % ARITY;
+                        let other = pos[slot];
+                        alone[qsize] = other;
+                        if t2count[other] >> 2 == 2 {
+                            qsize += 1;
+                        }
+                        t2count[other] = t2count[other].wrapping_sub(4) ^ slot as u8;
+                        t2hash[other] ^= hash;
+                    }
+                }
+                if stack_size + duplicates == size {
+                    break;
+                }
+            }
+
+            reverse_order[..size].fill(0);
+            t2count.fill(0);
+            t2hash.fill(0);
+            self.seed = splitmix64(&mut rng);
+        }
+
+        // assign fingerprints in reverse peeling order
+        for i in (0..stack_size).rev() {
+            let hash = reverse_order[i];
+            let pos = self.positions(hash);
+            let found = reverse_h[i] as usize;
+            let a = pos[(found + 1) % ARITY];
+            let b = pos[(found + 2) % ARITY];
+            self.fingerprints[pos[found]] = F::from_hash(hash) ^ self.fingerprints[a] ^ self.fingerprints[b];
+        }
+        Ok(())
+    }
+
+    /// Test whether an item is *probably* in the set.
+    pub fn contains<T: Hash>(&self, item: &T) -> bool {
+        let hash = murmur64(self.hasher_builder.hash_one(item).wrapping_add(self.seed));
+        let [h0, h1, h2] = self.positions(hash);
+        F::from_hash(hash) ^ self.fingerprints[h0] ^ self.fingerprints[h1] ^ self.fingerprints[h2] == F::default()
+    }
+
+    /// Number of keys the filter was built from.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// `true` if built from an empty key set.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Fingerprint width in bits.
+    pub fn fingerprint_bits(&self) -> u32 {
+        F::BITS
+    }
+
+    /// Size of the fingerprint array in bytes.
+    pub fn size_in_bytes(&self) -> usize {
+        self.fingerprints.len() * (F::BITS as usize / 8)
+    }
+
+    /// Storage cost per key in bits.
+    pub fn bits_per_entry(&self) -> f64 {
+        (self.size_in_bytes() * 8) as f64 / self.len.max(1) as f64
+    }
+
+    /// Serialize the filter into a byte vector.
+    ///
+    /// Layout:
+    ///   fingerprints (LE) + seed (u64 LE) + keys (u64 LE) + segment length (u32 LE)
+    ///   + segment count (u32 LE) + fingerprint bits (u32 LE)
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(self.size_in_bytes() + 28);
+        for &fp in &self.fingerprints {
+            fp.write_le(&mut out);
+        }
+        out.extend_from_slice(&self.seed.to_le_bytes());
+        out.extend_from_slice(&(self.len as u64).to_le_bytes());
+        out.extend_from_slice(&self.segment_length.to_le_bytes());
+        out.extend_from_slice(&(self.segment_count_length / self.segment_length).to_le_bytes());
+        out.extend_from_slice(&F::BITS.to_le_bytes());
+        out
+    }
+
+    /// Deserialize from bytes with the hasher builder used to build the filter.
+    ///
+    /// Returns `None` if the data length, fingerprint width or internal layout is invalid.
+    pub fn from_bytes_hasher(data: &[u8], hasher_builder: S) -> Option<Self> {
+        if data.len() < 28 { return None; }
+        let meta = data.len() - 28;
+        let seed = u64::from_le_bytes(data[meta..meta + 8].try_into().unwrap());
+        let len = u64::from_le_bytes(data[meta + 8..meta + 16].try_into().unwrap()) as usize;
+        let segment_length = u32::from_le_bytes(data[meta + 16..meta + 20].try_into().unwrap());
+        let segment_count = u32::from_le_bytes(data[meta + 20..meta + 24].try_into().unwrap());
+        let bits = u32::from_le_bytes(data[meta + 24..].try_into().unwrap());
+        if bits != F::BITS || !segment_length.is_power_of_two() || segment_count == 0 { return None; }
+
+        let array_length = (segment_count as usize + ARITY - 1) * segment_length as usize;
+        let width = F::BITS as usize / 8;
+        if meta != array_length * width { return None; }
+        let fingerprints = data[..meta].chunks_exact(width).map(F::read_le).collect();
+        Some(Self {
+            fingerprints,
+            seed,
+            segment_length,
+            segment_count_length: segment_count.checked_mul(segment_length)?,
+            len,
+            hasher_builder,
+        })
+    }
+}
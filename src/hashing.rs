@@ -1,4 +1,6 @@
+use core::any::TypeId;
 use core::hash::{BuildHasher, Hash, Hasher};
+use core::marker::PhantomData;
 
 /// Generates two hash values from a single item using a `BuildHasher`.
 /// This is a form of double hashing, useful for bloom filters.
@@ -17,4 +19,175 @@ pub fn hash2<T: Hash, S: BuildHasher>(state: &S, item: &T) -> (u64, u64) {
     }
 
     (v1, v2)
-}
\ No newline at end of file
+}
+
+/// Hasher builder with a 128-bit digest, so a filter can get both probe hashes from one pass over the item.
+///
+/// The crate's own implementors, `SeededSip13` (SipHash-1-3-128) and
+/// `SeededXxh3` (xxh3-128), are picked up by the normal constructors
+/// (`BloomFilter::with_hasher` and friends). Other implementors opt in
+/// through `BloomFilter::with_hasher_128`; everything else uses `hash2`.
+pub trait Hash128Builder: BuildHasher {
+    /// 128-bit digest of `item`.
+    fn hash_one_128<T: Hash + ?Sized>(&self, item: &T) -> u128;
+}
+
+/// Object-safe stand-in for `Hash`, so a digest function can be kept as a plain fn pointer.
+pub(crate) trait DynHash {
+    fn hash_dyn(&self, state: &mut dyn Hasher);
+}
+
+impl<T: Hash> DynHash for T {
+    fn hash_dyn(&self, mut state: &mut dyn Hasher) {
+        self.hash(&mut state);
+    }
+}
+
+impl Hash for dyn DynHash + '_ {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.hash_dyn(state);
+    }
+}
+
+/// 128-bit digest a filter opted into (see `hash_pair`), stored without needing `S: Hash128Builder`.
+pub(crate) type Hash128Fn<S> = fn(&S, &dyn DynHash) -> u128;
+
+/// The `Hash128Fn` of a `Hash128Builder`.
+pub(crate) fn hash128_fn<S: Hash128Builder>() -> Hash128Fn<S> {
+    |state, item| state.hash_one_128(item)
+}
+
+/// The `Hash128Fn` the normal constructors use for `S`: the 128-bit digest
+/// when `S` is one of the crate's `Hash128Builder`s, `None` otherwise.
+///
+/// Decided by type so that `with_hasher` keeps its plain `BuildHasher` bound.
+pub(crate) fn auto_hash128<S: BuildHasher>() -> Option<Hash128Fn<S>> {
+    if is_type::<S, crate::seeded::SeededSip13>() {
+        // SAFETY: `S` is `SeededSip13` (checked above)
+        return Some(|state, item| unsafe { &*(state as *const S).cast::<crate::seeded::SeededSip13>() }.hash_one_128(item));
+    }
+    #[cfg(feature = "fast-xxh3")]
+    if is_type::<S, crate::seeded::SeededXxh3>() {
+        // SAFETY: `S` is `SeededXxh3` (checked above)
+        return Some(|state, item| unsafe { &*(state as *const S).cast::<crate::seeded::SeededXxh3>() }.hash_one_128(item));
+    }
+    None
+}
+
+/// Whether `S` is `T`, without requiring `S: 'static`.
+///
+/// `TypeId` ignores lifetimes, and `T` has no lifetime parameters, so equal
+/// ids mean `S` and `T` are the same type.
+fn is_type<S: ?Sized, T: 'static>() -> bool {
+    trait NonStaticAny {
+        fn type_id(&self) -> TypeId
+        where
+            Self: 'static;
+    }
+
+    impl<S: ?Sized> NonStaticAny for PhantomData<S> {
+        fn type_id(&self) -> TypeId
+        where
+            Self: 'static,
+        {
+            TypeId::of::<S>()
+        }
+    }
+
+    let marker = PhantomData::<S>;
+    // SAFETY: lifetimes are erased before codegen, so the 'static object
+    // calls the same `type_id` as the original one; only the id is read
+    let erased = unsafe { core::mem::transmute::<&dyn NonStaticAny, &(dyn NonStaticAny + 'static)>(&marker) };
+    erased.type_id() == TypeId::of::<T>()
+}
+
+/// Probe hashes of one key, computed once and reusable across filters.
+///
+/// Made by `hash_key` on a filter and consumed by `insert_hashed` and
+/// `contains_hashed` on any filter that hashes the same way: same hasher
+/// builder and the same pair derivation (see `BloomFilter::with_hasher_128`).
+/// `m`, `k` and the (pair-based) `IndexStrategy` may differ, since positions
+/// are derived from the pair only when probing. The builder's fingerprint
+/// travels along so debug builds catch a hash handed to the wrong filter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyHash {
+    h1: u64,
+    h2: u64,
+    fingerprint: u32,
+    hash128: bool,
+}
+
+impl KeyHash {
+    pub(crate) fn new((h1, h2): (u64, u64), fingerprint: u32, hash128: bool) -> Self {
+        Self { h1, h2, fingerprint, hash128 }
+    }
+
+    /// The `(h1, h2)` pair probe positions are derived from (see `hash_pair`).
+    pub fn pair(&self) -> (u64, u64) {
+        (self.h1, self.h2)
+    }
+
+    /// Debug-only check that the hash was made by a filter hashing like the caller.
+    #[inline]
+    pub(crate) fn debug_check(&self, fingerprint: u32, hash128: bool) {
+        debug_assert!(
+            self.fingerprint == fingerprint && self.hash128 == hash128,
+            "KeyHash made by an incompatible hasher builder"
+        );
+    }
+}
+
+/// Split a 128-bit digest into an `(h1, h2)` pair; `h2` is forced odd as in `hash2`.
+pub fn split128(digest: u128) -> (u64, u64) {
+    (digest as u64, (digest >> 64) as u64 | 1)
+}
+
+/// `(h1, h2)` for the pair-based index strategies.
+///
+/// Filters that opted into a 128-bit digest hash the item once and split
+/// it; all others use `hash2`.
+#[inline]
+pub(crate) fn hash_pair<T: Hash, S: BuildHasher>(state: &S, item: &T, hash128: Option<Hash128Fn<S>>) -> (u64, u64) {
+    match hash128 {
+        Some(digest) => split128(digest(state, item)),
+        None => hash2(state, item),
+    }
+}
+
+/// Derive the `i`-th probe index in `[0, m)` from a `hash2` pair.
+///
+/// Shared by every filter that uses the double-hashing scheme so their bit
+/// positions stay interchangeable.
+#[inline]
+pub(crate) fn nth_index(h1: u64, h2: u64, i: u32, m: usize) -> usize {
+    let combined = h1.wrapping_add((i as u64).wrapping_mul(h2));
+    (combined % (m as u64)) as usize
+}
+
+/// Fingerprint of a hasher builder: a fixed probe hashed through `state`.
+///
+/// Two builders that derive the same bit positions produce the same value;
+/// builders with different keys (e.g. two `RandomState`s) almost surely do
+/// not. Never zero, so `0` can mean "not recorded".
+pub(crate) fn hasher_fingerprint<S: BuildHasher>(state: &S) -> u32 {
+    let h = state.hash_one(0x626c_6f6f_6d7a_u64);
+    ((h ^ (h >> 32)) as u32).max(1)
+}
+
+/// SplitMix64 step: advances `state` and returns the next well-mixed value.
+pub(crate) fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// MurmurHash3 64-bit finalizer; a cheap bijective mix of `h`.
+pub(crate) fn murmur64(mut h: u64) -> u64 {
+    h ^= h >> 33;
+    h = h.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    h ^= h >> 33;
+    h = h.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+    h ^ (h >> 33)
+}
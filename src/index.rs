@@ -0,0 +1,177 @@
+use core::hash::{BuildHasher, Hash};
+
+use crate::hashing::{self, Hash128Fn, KeyHash};
+
+/// How a filter turns an item's hashes into its `k` bit positions.
+///
+/// Every strategy sets `k` bits per item; they differ in cost and in how
+/// close the probes come to being independent, which is what the textbook
+/// false-positive formula assumes.
+///
+/// | Strategy | Hashes per item | Reduction |
+/// |---|---|---|
+/// | `DoubleHashing` | 2 | `% m` |
+/// | `FastRange` | 2 | multiply-shift |
+/// | `EnhancedDoubleHashing` | 2 | multiply-shift, then `mod m` steps |
+/// | `TripleHashing` | 2 | multiply-shift, then `mod m` steps |
+/// | `Independent` | `k` | multiply-shift |
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum IndexStrategy {
+    /// `h1 + i*h2 mod m`: the original scheme, and the one filters written
+    /// before strategies existed use. Costs a 64-bit division per probe.
+    #[default]
+    DoubleHashing,
+    /// The same probe sequence as `DoubleHashing`, reduced into `[0, m)`
+    /// with Lemire's fastrange (`(h * m) >> 64`) instead of a division.
+    FastRange,
+    /// Enhanced double hashing (Dillinger & Manolios, 2004): `x += y; y += i`,
+    /// both taken `mod m` after reducing `h1` and `h2` with fastrange.
+    /// The cubic term breaks up the probe collisions plain double hashing
+    /// has when `h2` is small relative to `m`, which matters at large `k`.
+    EnhancedDoubleHashing,
+    /// Triple hashing (Dillinger & Manolios, 2004): `x += y; y += z`, all
+    /// `mod m`, with a third hash `z` mixed from the pair. Probes are as
+    /// far apart as with a third independent hash, at no extra hashing cost.
+    TripleHashing,
+    /// One hash of `(i, item)` per probe: truly independent positions at the
+    /// price of `k` hash computations per item.
+    Independent,
+}
+
+impl IndexStrategy {
+    /// Id stored in versioned files.
+    pub fn id(self) -> u8 {
+        match self {
+            IndexStrategy::DoubleHashing => 0,
+            IndexStrategy::FastRange => 1,
+            IndexStrategy::EnhancedDoubleHashing => 2,
+            IndexStrategy::Independent => 3,
+            IndexStrategy::TripleHashing => 4,
+        }
+    }
+
+    /// Strategy for a stored id, if known.
+    pub fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(IndexStrategy::DoubleHashing),
+            1 => Some(IndexStrategy::FastRange),
+            2 => Some(IndexStrategy::EnhancedDoubleHashing),
+            3 => Some(IndexStrategy::Independent),
+            4 => Some(IndexStrategy::TripleHashing),
+            _ => None,
+        }
+    }
+
+    /// Whether positions are derived from an `(h1, h2)` pair (all but `Independent`).
+    pub fn uses_hash_pair(self) -> bool {
+        self != IndexStrategy::Independent
+    }
+
+    /// Visit the item's bit positions in `[0, m)` until `visit` returns `false`.
+    ///
+    /// Returns `true` if every probe was visited. Membership tests stop at the
+    /// first clear bit; inserts visit with a closure that always continues.
+    /// `hash128` selects the pair derivation, see `hashing::hash_pair`.
+    #[inline]
+    pub(crate) fn probe<T: Hash, S: BuildHasher>(
+        self,
+        state: &S,
+        item: &T,
+        hash128: Option<Hash128Fn<S>>,
+        k: u32,
+        m: usize,
+        visit: impl FnMut(usize) -> bool,
+    ) -> bool {
+        if self.uses_hash_pair() {
+            let (h1, h2) = hashing::hash_pair(state, item, hash128);
+            self.probe_pair(h1, h2, k, m, visit)
+        } else {
+            self.probe_independent(state, item, k, m, visit)
+        }
+    }
+
+    /// Like `probe`, reusing an already computed `hashing::hash_pair` when the strategy allows.
+    #[inline]
+    pub(crate) fn probe_prehashed<T: Hash, S: BuildHasher>(
+        self,
+        state: &S,
+        item: &T,
+        (h1, h2): (u64, u64),
+        k: u32,
+        m: usize,
+        visit: impl FnMut(usize) -> bool,
+    ) -> bool {
+        if self.uses_hash_pair() {
+            self.probe_pair(h1, h2, k, m, visit)
+        } else {
+            self.probe_independent(state, item, k, m, visit)
+        }
+    }
+
+    /// Probe from a `KeyHash`; panics for `Independent`, whose probes are hashed from the item.
+    #[inline]
+    pub(crate) fn probe_hashed(self, hash: KeyHash, k: u32, m: usize, visit: impl FnMut(usize) -> bool) -> bool {
+        assert!(self.uses_hash_pair(), "IndexStrategy::Independent filters cannot use pre-hashed keys");
+        let (h1, h2) = hash.pair();
+        self.probe_pair(h1, h2, k, m, visit)
+    }
+
+    /// Pair-based strategies only; `Independent` needs the item itself.
+    #[inline]
+    pub(crate) fn probe_pair(self, h1: u64, h2: u64, k: u32, m: usize, mut visit: impl FnMut(usize) -> bool) -> bool {
+        match self {
+            IndexStrategy::DoubleHashing => (0..k).all(|i| visit(hashing::nth_index(h1, h2, i, m))),
+            IndexStrategy::FastRange => {
+                (0..k).all(|i| visit(fastrange(h1.wrapping_add((i as u64).wrapping_mul(h2)), m)))
+            }
+            // the recurrences must run mod m: stepping the full 64-bit values
+            // and reducing with fastrange would only move the low bits, which
+            // fastrange drops, and give back the `FastRange` probes
+            IndexStrategy::EnhancedDoubleHashing => {
+                let (mut x, mut y) = (fastrange(h1, m), fastrange(h2, m));
+                (0..k).all(|i| {
+                    let idx = x;
+                    x = add_mod(x, y, m);
+                    y = add_mod(y, (i as usize + 1) % m, m);
+                    visit(idx)
+                })
+            }
+            IndexStrategy::TripleHashing => {
+                let z = fastrange(hashing::murmur64(h1 ^ h2.rotate_left(32)), m);
+                let (mut x, mut y) = (fastrange(h1, m), fastrange(h2, m));
+                (0..k).all(|_| {
+                    let idx = x;
+                    x = add_mod(x, y, m);
+                    y = add_mod(y, z, m);
+                    visit(idx)
+                })
+            }
+            IndexStrategy::Independent => unreachable!("independent probes are derived from the item"),
+        }
+    }
+
+    #[inline]
+    fn probe_independent<T: Hash, S: BuildHasher>(
+        self,
+        state: &S,
+        item: &T,
+        k: u32,
+        m: usize,
+        mut visit: impl FnMut(usize) -> bool,
+    ) -> bool {
+        (0..k).all(|i| visit(fastrange(state.hash_one((i, item)), m)))
+    }
+}
+
+/// `(a + b) mod m` for `a, b < m`, without a division.
+#[inline]
+fn add_mod(a: usize, b: usize, m: usize) -> usize {
+    let s = a + b;
+    if s >= m { s - m } else { s }
+}
+
+/// Lemire's fastrange: map `h` uniformly into `[0, m)` with a multiply and a shift.
+#[inline]
+pub(crate) fn fastrange(h: u64, m: usize) -> usize {
+    ((h as u128 * m as u128) >> 64) as usize
+}
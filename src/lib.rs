@@ -7,11 +7,71 @@ extern crate std;
 
 /// A bitset implementation for the bloom filter.
 pub mod bitset;
+/// Runtime-dispatched SIMD kernels behind the `BitSet` bulk operations.
+mod simd;
 /// Mathematical functions for calculating optimal bloom filter parameters.
 pub mod math;
 /// Hashing functions for the bloom filter.
 pub mod hashing;
+/// Strategies for deriving bit positions from an item's hashes.
+pub mod index;
+/// Seeded, platform-stable hasher builders for filters that are persisted.
+pub mod seeded;
+/// Versioned, checksummed binary container for persisted filters.
+pub mod format;
 /// The bloom filter implementation.
 pub mod bloom;
+/// Zero-copy bloom filter view over borrowed bytes.
+pub mod view;
+/// Memory-mapped, file-backed bloom filters (requires "mmap" feature, Linux).
+#[cfg(all(feature = "mmap", target_os = "linux"))]
+pub mod mmap;
+/// Counting bloom filter with removal support.
+pub mod counting;
+/// Scalable bloom filter that grows past its design capacity.
+pub mod scalable;
+/// Cache-line blocked bloom filter.
+pub mod blocked;
+/// Partitioned bloom filter with one slice per hash function.
+pub mod partitioned;
+/// Cuckoo filter with deletion support.
+pub mod cuckoo;
+/// Quotient filter with resize and merge.
+pub mod quotient;
+/// Static binary fuse filter for immutable key sets.
+pub mod fuse;
+/// Static ribbon filter with near-optimal space at any false-positive rate.
+pub mod ribbon;
+/// Stable bloom filter for deduplicating unbounded streams.
+pub mod stable;
+/// Time-aging bloom filter built from a ring of generations.
+pub mod aging;
+/// Lock-free bloom filter that inserts through a shared reference.
+pub mod atomic;
+/// Bloom filter split into independently locked, serializable shards.
+pub mod sharded;
 
-pub use bloom::BloomFilter;
\ No newline at end of file
+pub use bloom::{BloomError, BloomFilter};
+pub use hashing::{Hash128Builder, KeyHash};
+pub use index::IndexStrategy;
+pub use format::{FilterKind, FormatError, Header};
+pub use view::BloomFilterRef;
+#[cfg(all(feature = "mmap", target_os = "linux"))]
+pub use mmap::{MmapBloomFilter, MmapBloomFilterMut};
+pub use seeded::{PersistentHasher, SeededSip13, SipHasher13};
+#[cfg(feature = "fast-xxh3")]
+pub use seeded::SeededXxh3;
+#[cfg(feature = "fast-ahash")]
+pub use seeded::SeededAHash;
+pub use counting::{CountingBloomFilter, CountingError};
+pub use scalable::ScalableBloomFilter;
+pub use blocked::{BlockSize, BlockedBloomFilter};
+pub use partitioned::PartitionedBloomFilter;
+pub use cuckoo::{CuckooError, CuckooFilter};
+pub use quotient::{QuotientError, QuotientFilter};
+pub use fuse::{BinaryFuse16, BinaryFuse8, BinaryFuseFilter, FuseError};
+pub use ribbon::{RibbonError, RibbonFilter};
+pub use stable::StableBloomFilter;
+pub use aging::{AgingBloomFilter, Clock, ManualClock, Rotation, SystemClock};
+pub use atomic::AtomicBloomFilter;
+pub use sharded::{ShardStats, ShardedBloomFilter};
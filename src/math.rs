@@ -14,3 +14,156 @@ pub fn optimal_k(m: usize, n: usize) -> u32 {
     assert!(m > 0 && n > 0);
     (((m as f64 / n as f64) * std::f64::consts::LN_2).round() as u32).max(1)
 }
+
+/// estimated number of distinct items in a standard bloom filter with `m`
+/// bits, `k` hashes and `ones` bits set (Swamidass & Baldi):
+/// n = -(m / k) * ln(1 - ones / m)
+/// infinite once every bit is set.
+pub fn estimated_cardinality(m: usize, k: u32, ones: usize) -> f64 {
+    assert!(m > 0 && k > 0 && ones <= m);
+    -(m as f64 / k as f64) * (1.0 - ones as f64 / m as f64).ln()
+}
+
+/// expected false positive rate of a blocked bloom filter with `m` bits split
+/// into blocks of `block_bits`, holding `n` items with `k` probes per item.
+///
+/// items land in blocks following a Poisson distribution with mean
+/// `n * block_bits / m`; each block then behaves like a small standard filter:
+/// fpr = sum_i Pois(i) * (1 - (1 - 1/B)^(k*i))^k
+pub fn blocked_fpr(m: usize, n: usize, k: u32, block_bits: usize) -> f64 {
+    assert!(m > 0 && k > 0 && block_bits > 0);
+    if n == 0 {
+        return 0.0;
+    }
+    let lambda = n as f64 * block_bits as f64 / m as f64;
+    let miss = 1.0 - 1.0 / block_bits as f64;
+    let upper = (lambda + 10.0 * lambda.sqrt() + 20.0).ceil() as u64;
+    // pois(i) computed in log-space to stay finite for large lambda
+    let mut ln_fact = 0.0f64;
+    let mut total = 0.0f64;
+    for i in 0..=upper {
+        if i > 0 {
+            ln_fact += (i as f64).ln();
+        }
+        let pois = (i as f64 * lambda.ln() - lambda - ln_fact).exp();
+        let inner = 1.0 - miss.powf(k as f64 * i as f64);
+        total += pois * inner.powi(k as i32);
+    }
+    total.min(1.0)
+}
+
+/// smallest bit count `m` (a multiple of `block_bits`) for which a blocked filter
+/// holding `n` items stays at or below false positive rate `p`, with `k` from `optimal_k`.
+///
+/// blocking costs a few percent more space than `optimal_m` at the same `p`.
+pub fn optimal_blocked_m(n: usize, p: f64, block_bits: usize) -> usize {
+    assert!(block_bits > 0, "block_bits must be > 0");
+    let mut m = optimal_m(n, p).div_ceil(block_bits) * block_bits;
+    loop {
+        let k = optimal_k(m, n);
+        if blocked_fpr(m, n, k, block_bits) <= p {
+            return m;
+        }
+        let step = (m / 64).max(block_bits);
+        m = (m + step).div_ceil(block_bits) * block_bits;
+    }
+}
+
+/// expected false positive rate of a partitioned bloom filter: `m` bits split
+/// into `k` slices of `m/k` bits, each receiving one probe per item.
+/// formula: fpr = (1 - (1 - k/m)^n)^k
+pub fn partitioned_fpr(m: usize, n: usize, k: u32) -> f64 {
+    assert!(m > 0 && k > 0 && (k as usize) <= m);
+    let slice_fill = 1.0 - (1.0 - k as f64 / m as f64).powf(n as f64);
+    slice_fill.powi(k as i32)
+}
+
+/// fingerprint width (bits) a cuckoo filter with `bucket_size` slots per
+/// bucket needs to stay at or below false positive rate `p`.
+/// formula: f = ceil(log2(2b / p))
+pub fn cuckoo_fingerprint_bits(p: f64, bucket_size: usize) -> u32 {
+    assert!(p > 0.0 && p < 1.0, "p must be in (0,1)");
+    assert!(bucket_size > 0, "bucket_size must be > 0");
+    ((2.0 * bucket_size as f64 / p).log2().ceil() as u32).max(2)
+}
+
+/// remainder width `r` a quotient filter needs for false positive rate `p`.
+/// at load factor α the rate is about α / 2^r, so r = ceil(log2(1/p)) keeps
+/// the filter at or below `p` at any load.
+pub fn quotient_remainder_bits(p: f64) -> u32 {
+    assert!(p > 0.0 && p < 1.0, "p must be in (0,1)");
+    ((-p.log2()).ceil() as u32).max(1)
+}
+
+/// expected false positive rate of a quotient filter with `r` remainder bits
+/// at load factor `load` (entries / slots).
+/// formula: fpr = 1 - e^(-load / 2^r)
+pub fn quotient_fpr(load: f64, r: u32) -> f64 {
+    1.0 - (-load / 2f64.powi(r as i32)).exp()
+}
+
+/// number of solution rows ("slots") a standard ribbon filter with 64-bit
+/// coefficient rows allocates for `n` keys, rounded up to whole 64-slot blocks.
+///
+/// banding needs slack that grows with log2(n) for a fixed band width:
+/// ~5% up to 2^15 keys, ~10% at a million, plus one block for tiny sets.
+pub fn ribbon_num_slots(n: usize) -> usize {
+    let overhead = ((n.max(1) as f64).log2() - 10.0).max(5.0) / 100.0;
+    let slots = (n as f64 * (1.0 + overhead)).ceil() as usize + 64;
+    slots.div_ceil(64).max(2) * 64
+}
+
+/// average result bits per slot a ribbon filter stores for false positive rate `p`.
+///
+/// every block keeps r = floor(log2(1/p)) columns and a fraction q of blocks keep
+/// one more, so that (1 - q) 2^-r + q 2^-(r+1) = p: r + 2(1 - p 2^r).
+/// at least 1 bit per slot.
+pub fn ribbon_result_bits(p: f64) -> f64 {
+    assert!(p > 0.0 && p < 1.0, "p must be in (0,1)");
+    let r = (-p.log2()).floor().max(1.0);
+    let extra = (2.0 * (1.0 - p * 2f64.powf(r))).clamp(0.0, 1.0);
+    r + extra
+}
+
+/// expected space of a ribbon filter in bits per key for `n` keys at false
+/// positive rate `p` (solution bits only, excluding the fixed trailer).
+///
+/// compare with `optimal_m(n, p) / n` for a bloom filter: ribbon needs
+/// 1.05-1.12 * log2(1/p) bits/key versus 1.44 * log2(1/p).
+pub fn ribbon_bits_per_key(n: usize, p: f64) -> f64 {
+    assert!(n > 0, "n must be > 0");
+    ribbon_num_slots(n) as f64 * ribbon_result_bits(p) / n as f64
+}
+
+/// hash count for a stable bloom filter targeting false positive rate `p`:
+/// k = ceil(log2(1/p)), so about half the cells are zero at the stable point.
+pub fn stable_k(p: f64) -> u32 {
+    assert!(p > 0.0 && p < 1.0, "p must be in (0,1)");
+    ((-p.log2()).ceil() as u32).max(1)
+}
+
+/// false positive rate a stable bloom filter converges to (Deng & Rafiei, 2006),
+/// with `m` cells of `cell_bits` bits, `k` hashes and `decrements` cells
+/// decremented per insert. max = 2^cell_bits - 1:
+/// zeros = (1 / (1 + 1 / (P (1/k - 1/m))))^max, fpr = (1 - zeros)^k
+pub fn stable_fpr(m: usize, k: u32, cell_bits: u32, decrements: usize) -> f64 {
+    assert!(m > 0 && k > 0 && (k as usize) <= m);
+    assert!((1..=8).contains(&cell_bits), "cell_bits must be in 1..=8");
+    let max = ((1u32 << cell_bits) - 1) as f64;
+    let spread = 1.0 / k as f64 - 1.0 / m as f64;
+    let zeros = (1.0 / (1.0 + 1.0 / (decrements as f64 * spread))).powf(max);
+    (1.0 - zeros).powi(k as i32)
+}
+
+/// fewest cells to decrement per insert so a stable bloom filter settles at or
+/// below false positive rate `p` (inverse of `stable_fpr`, at least 1):
+/// P = 1 / ((1 / (1 - p^(1/k))^(1/max) - 1) (1/k - 1/m))
+pub fn stable_decrements(m: usize, k: u32, cell_bits: u32, p: f64) -> usize {
+    assert!(p > 0.0 && p < 1.0, "p must be in (0,1)");
+    assert!(m > 0 && k > 0 && (k as usize) < m);
+    assert!((1..=8).contains(&cell_bits), "cell_bits must be in 1..=8");
+    let max = ((1u32 << cell_bits) - 1) as f64;
+    let zeros = 1.0 - p.powf(1.0 / k as f64);
+    let denom = (1.0 / zeros.powf(1.0 / max) - 1.0) * (1.0 / k as f64 - 1.0 / m as f64);
+    ((1.0 / denom).ceil() as usize).max(1)
+}
@@ -0,0 +1,322 @@
+use core::hash::{BuildHasher, Hash};
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::path::Path;
+
+use memmap2::{Mmap, MmapMut};
+
+use crate::format::{self, FilterKind, FormatError, Header, Resolved, CHECKSUM_LEN, HEADER_LEN, ITEMS_OFFSET};
+use crate::seeded::PersistentHasher;
+use crate::index::IndexStrategy;
+use crate::view::contains_in;
+use crate::hashing::{self, Hash128Fn};
+use crate::math;
+
+/// Validate a mapped versioned file without reading the payload.
+fn validate<S: PersistentHasher>(data: &[u8], builder: Option<S>) -> Result<(Header, usize, Resolved<S>), FormatError> {
+    let (header, payload) = format::open_unchecked(data)?;
+    let resolved = format::resolve_hasher(&header, builder)?;
+    let m = format::bloom_bits(&header, payload)?;
+    Ok((header, m, resolved))
+}
+
+fn invalid_input(why: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, why)
+}
+
+/// Read-only bloom filter queried straight from a memory-mapped file (requires "mmap" feature, Linux).
+///
+/// The file uses the versioned layout written by
+/// `BloomFilter::to_versioned_bytes`. Opening only parses the header and
+/// checks the file length, so it takes the same time for any filter size;
+/// pages are faulted in by the kernel as queries touch them. Call `verify`
+/// to check the CRC32C (reads the whole file).
+pub struct MmapBloomFilter<S = std::collections::hash_map::RandomState> {
+    map: Mmap,
+    m: usize,
+    k: u32,
+    items: usize,
+    strategy: IndexStrategy,
+    hash128: Option<Hash128Fn<S>>,
+    hasher_builder: S,
+}
+
+impl<S> fmt::Debug for MmapBloomFilter<S>
+where
+    S: BuildHasher + Clone,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MmapBloomFilter")
+            .field("m(bits)", &self.m)
+            .field("k", &self.k)
+            .field("items", &self.items)
+            .finish()
+    }
+}
+
+impl<S> MmapBloomFilter<S>
+where
+    S: PersistentHasher,
+{
+    /// Map `path` read-only, rebuilding the hasher from the stored seed.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::map(path.as_ref(), None)
+    }
+
+    /// Map `path` read-only with an explicit hasher builder (required for `ID == 0` builders).
+    pub fn open_hasher(path: impl AsRef<Path>, hasher_builder: S) -> io::Result<Self> {
+        Self::map(path.as_ref(), Some(hasher_builder))
+    }
+
+    fn map(path: &Path, builder: Option<S>) -> io::Result<Self> {
+        let file = File::open(path)?;
+        // SAFETY: the mapping is only read through `&[u8]`; as with any mmap,
+        // another process truncating the file while it is mapped is undefined.
+        let map = unsafe { Mmap::map(&file)? };
+        let (header, m, (hasher_builder, hash128)) = validate(&map, builder).map_err(format::io_error)?;
+        Ok(Self {
+            map,
+            m,
+            k: header.k,
+            items: header.items as usize,
+            strategy: header.strategy,
+            hash128,
+            hasher_builder,
+        })
+    }
+
+    /// Check whether an item is probably in the set.
+    pub fn contains<T: Hash>(&self, item: &T) -> bool {
+        contains_in(self.payload(), self.m, self.k, self.strategy, self.hash128, &self.hasher_builder, item)
+    }
+
+    /// Verify the stored CRC32C against the mapped contents.
+    pub fn verify(&self) -> Result<(), FormatError> {
+        format::verify_checksum(&self.map)
+    }
+
+    fn payload(&self) -> &[u8] {
+        &self.map[HEADER_LEN..self.map.len() - CHECKSUM_LEN]
+    }
+
+    /// Number of bits (`m`).
+    pub fn num_bits(&self) -> usize {
+        self.m
+    }
+
+    /// Number of hash functions (`k`).
+    pub fn num_hashes(&self) -> u32 {
+        self.k
+    }
+
+    /// Item counter stored in the file header.
+    pub fn approximate_items(&self) -> usize {
+        self.items
+    }
+
+    /// Strategy used to derive bit positions.
+    pub fn index_strategy(&self) -> IndexStrategy {
+        self.strategy
+    }
+
+    /// Hasher builder used to derive bit positions.
+    pub fn hasher_builder(&self) -> &S {
+        &self.hasher_builder
+    }
+}
+
+/// Read-write bloom filter backed by a memory-mapped file (requires "mmap" feature, Linux).
+///
+/// Inserts set bits directly in the mapping. `flush` writes the item counter
+/// and a fresh CRC32C into the file and syncs it to disk; until then the
+/// on-disk checksum is stale and readers opening the file with `verify`
+/// will reject it. The checksum pass reads the whole payload, so `flush` is
+/// O(m) while `insert` and `contains` stay O(k). Only one writer may map a
+/// file at a time.
+pub struct MmapBloomFilterMut<S = std::collections::hash_map::RandomState> {
+    map: MmapMut,
+    m: usize,
+    k: u32,
+    items: usize,
+    strategy: IndexStrategy,
+    hash128: Option<Hash128Fn<S>>,
+    hasher_builder: S,
+}
+
+impl<S> fmt::Debug for MmapBloomFilterMut<S>
+where
+    S: BuildHasher + Clone,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MmapBloomFilterMut")
+            .field("m(bits)", &self.m)
+            .field("k", &self.k)
+            .field("items", &self.items)
+            .finish()
+    }
+}
+
+impl<S> MmapBloomFilterMut<S>
+where
+    S: PersistentHasher,
+{
+    /// Create (or truncate) `path` as an empty filter with `m` bits and `k` hashes.
+    ///
+    /// Fails with `ErrorKind::InvalidInput` if `m` or `k` is zero or `k`
+    /// exceeds `format::MAX_K`; the file is left untouched then.
+    pub fn create(path: impl AsRef<Path>, m: usize, k: u32, hasher_builder: S) -> io::Result<Self> {
+        Self::create_file(path.as_ref(), m, k, (0, 0.0), hasher_builder, IndexStrategy::default())
+    }
+
+    /// Like `create`, with an explicit `IndexStrategy`.
+    pub fn create_with_strategy(
+        path: impl AsRef<Path>,
+        m: usize,
+        k: u32,
+        hasher_builder: S,
+        strategy: IndexStrategy,
+    ) -> io::Result<Self> {
+        Self::create_file(path.as_ref(), m, k, (0, 0.0), hasher_builder, strategy)
+    }
+
+    /// Create (or truncate) `path` sized for capacity `n` at false-positive rate `p`.
+    ///
+    /// Fails with `ErrorKind::InvalidInput` if `n` is zero or `p` is outside `(0, 1)`.
+    pub fn create_for_capacity(path: impl AsRef<Path>, n: usize, p: f64, hasher_builder: S) -> io::Result<Self> {
+        if n == 0 {
+            return Err(invalid_input("capacity must be > 0"));
+        }
+        if !(p > 0.0 && p < 1.0) {
+            return Err(invalid_input("false-positive rate must be in (0, 1)"));
+        }
+        let m = math::optimal_m(n, p);
+        let k = math::optimal_k(m, n);
+        Self::create_file(path.as_ref(), m, k, (n, p), hasher_builder, IndexStrategy::default())
+    }
+
+    fn create_file(
+        path: &Path,
+        m: usize,
+        k: u32,
+        design: (usize, f64),
+        hasher_builder: S,
+        strategy: IndexStrategy,
+    ) -> io::Result<Self> {
+        if m == 0 {
+            return Err(invalid_input("m must be > 0"));
+        }
+        if k == 0 || k > format::MAX_K {
+            return Err(invalid_input("k must be in 1..=format::MAX_K"));
+        }
+        let hash128 = hashing::auto_hash128::<S>();
+        let header = Header {
+            version: format::VERSION,
+            kind: FilterKind::Bloom,
+            hasher_id: S::ID,
+            seed: hasher_builder.seed(),
+            m: m as u64,
+            k,
+            strategy,
+            hash128: hash128.is_some(),
+            fingerprint: hashing::hasher_fingerprint(&hasher_builder),
+            items: 0,
+            capacity: design.0 as u64,
+            fpr: design.1,
+            payload_len: m.div_ceil(64) as u64 * 8,
+        };
+        let file = OpenOptions::new().read(true).write(true).create(true).truncate(true).open(path)?;
+        // the extended region reads as zeros, i.e. an empty payload
+        file.set_len(header.total_len().expect("payload length of an in-memory filter fits in u64"))?;
+        // SAFETY: see `MmapBloomFilter::map`; we hold the only writable mapping.
+        let mut map = unsafe { MmapMut::map_mut(&file)? };
+        map[..HEADER_LEN].copy_from_slice(&header.to_bytes());
+        let mut bf = Self { map, m, k, items: 0, strategy, hash128, hasher_builder };
+        bf.flush()?;
+        Ok(bf)
+    }
+
+    /// Map an existing file read-write, rebuilding the hasher from the stored seed.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::map(path.as_ref(), None)
+    }
+
+    /// Map an existing file read-write with an explicit hasher builder.
+    pub fn open_hasher(path: impl AsRef<Path>, hasher_builder: S) -> io::Result<Self> {
+        Self::map(path.as_ref(), Some(hasher_builder))
+    }
+
+    fn map(path: &Path, builder: Option<S>) -> io::Result<Self> {
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+        // SAFETY: see `MmapBloomFilter::map`; we hold the only writable mapping.
+        let map = unsafe { MmapMut::map_mut(&file)? };
+        let (header, m, (hasher_builder, hash128)) = validate(&map, builder).map_err(format::io_error)?;
+        Ok(Self {
+            map,
+            m,
+            k: header.k,
+            items: header.items as usize,
+            strategy: header.strategy,
+            hash128,
+            hasher_builder,
+        })
+    }
+
+    /// Insert an item, setting its bits in the mapping.
+    pub fn insert<T: Hash>(&mut self, item: &T) {
+        let payload = &mut self.map[HEADER_LEN..HEADER_LEN + self.m.div_ceil(64) * 8];
+        self.strategy.probe(&self.hasher_builder, item, self.hash128, self.k, self.m, |idx| {
+            payload[idx >> 3] |= 1 << (idx & 7);
+            true
+        });
+        self.items = self.items.saturating_add(1);
+    }
+
+    /// Check whether an item is probably in the set.
+    pub fn contains<T: Hash>(&self, item: &T) -> bool {
+        let payload = &self.map[HEADER_LEN..self.map.len() - CHECKSUM_LEN];
+        contains_in(payload, self.m, self.k, self.strategy, self.hash128, &self.hasher_builder, item)
+    }
+
+    /// Verify the stored CRC32C against the mapped contents.
+    ///
+    /// Inserts since the last `flush` leave the checksum stale, so this fails
+    /// with `FormatError::BadChecksum` until the next `flush`.
+    pub fn verify(&self) -> Result<(), FormatError> {
+        format::verify_checksum(&self.map)
+    }
+
+    /// Write the item counter and checksum, then sync the mapping to disk.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.map[ITEMS_OFFSET..ITEMS_OFFSET + 8].copy_from_slice(&(self.items as u64).to_le_bytes());
+        let body = self.map.len() - CHECKSUM_LEN;
+        let crc = format::crc32c(&self.map[..body]);
+        self.map[body..].copy_from_slice(&crc.to_le_bytes());
+        self.map.flush()
+    }
+
+    /// Number of bits (`m`).
+    pub fn num_bits(&self) -> usize {
+        self.m
+    }
+
+    /// Number of hash functions (`k`).
+    pub fn num_hashes(&self) -> u32 {
+        self.k
+    }
+
+    /// Approximate number of inserted items (including duplicates).
+    pub fn approximate_items(&self) -> usize {
+        self.items
+    }
+
+    /// Strategy used to derive bit positions.
+    pub fn index_strategy(&self) -> IndexStrategy {
+        self.strategy
+    }
+
+    /// Hasher builder used to derive bit positions.
+    pub fn hasher_builder(&self) -> &S {
+        &self.hasher_builder
+    }
+}
@@ -0,0 +1,209 @@
+use core::hash::{BuildHasher, Hash};
+
+use std::fmt;
+
+use crate::{bitset::BitSet, hashing, math, simd};
+
+/// Trailer closing `to_bytes` output, so `BloomFilter` readers refuse it.
+const TAG: [u8; 4] = *b"PBLM";
+
+/// Partitioned bloom filter: one slice per hash function.
+///
+/// The `m` bits are split into `k` equal, word-aligned slices and probe `i`
+/// only ever lands in slice `i`. Each item therefore sets exactly one bit
+/// per slice, the slices fill independently, and the false-positive rate is
+/// the product of the per-slice fill ratios.
+#[derive(Clone)]
+pub struct PartitionedBloomFilter<S = std::collections::hash_map::RandomState> {
+    bits: BitSet,
+    slice_bits: usize,
+    k: u32,
+    items: usize,
+    hasher_builder: S,
+}
+
+impl<S> fmt::Debug for PartitionedBloomFilter<S>
+where
+    S: BuildHasher + Clone,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PartitionedBloomFilter")
+            .field("m(bits)", &self.bits.len_bits())
+            .field("slice_bits", &self.slice_bits)
+            .field("k", &self.k)
+            .field("items", &self.items)
+            .finish()
+    }
+}
+
+impl PartitionedBloomFilter<std::collections::hash_map::RandomState> {
+    /// convenience constructor using default hasher builder.
+    pub fn new(m: usize, k: u32) -> Self {
+        Self::with_hasher(m, k, std::collections::hash_map::RandomState::new())
+    }
+
+    /// convenience constructor from capacity and false-positive rate with default hasher.
+    pub fn new_for_capacity(n: usize, p: f64) -> Self {
+        let m = math::optimal_m(n, p);
+        let k = math::optimal_k(m, n);
+        Self::with_hasher(m, k, std::collections::hash_map::RandomState::new())
+    }
+}
+
+impl<S> PartitionedBloomFilter<S>
+where
+    S: BuildHasher + Clone,
+{
+    /// create with explicit hasher builder.
+    ///
+    /// Each slice is rounded up to a multiple of 64 bits, so the final `m`
+    /// may be slightly larger than requested.
+    pub fn with_hasher(m: usize, k: u32, hasher_builder: S) -> Self {
+        assert!(m > 0 && k > 0);
+        let slice_bits = m.div_ceil(k as usize).div_ceil(64) * 64;
+        Self {
+            bits: BitSet::new(slice_bits * k as usize),
+            slice_bits,
+            k,
+            items: 0,
+            hasher_builder,
+        }
+    }
+
+    /// Insert an item, setting one bit in each of the `k` slices.
+    pub fn insert<T: Hash>(&mut self, item: &T) {
+        let (h1, h2) = hashing::hash2(&self.hasher_builder, item);
+        for i in 0..self.k {
+            let idx = i as usize * self.slice_bits + hashing::nth_index(h1, h2, i, self.slice_bits);
+            self.bits.set(idx);
+        }
+        self.items = self.items.saturating_add(1);
+    }
+
+    /// Test whether an item is *probably* in the set.
+    pub fn contains<T: Hash>(&self, item: &T) -> bool {
+        let (h1, h2) = hashing::hash2(&self.hasher_builder, item);
+        (0..self.k).all(|i| {
+            self.bits.get(i as usize * self.slice_bits + hashing::nth_index(h1, h2, i, self.slice_bits))
+        })
+    }
+
+    /// In‑place union (bitwise OR) with another filter.
+    ///
+    /// Both filters must have identical `m` and `k` parameters.
+    pub fn union_inplace(&mut self, other: &Self) {
+        assert_eq!(self.bits.len_bits(), other.bits.len_bits(), "m mismatch for union");
+        assert_eq!(self.k, other.k, "k mismatch for union");
+        self.bits.or_with(&other.bits);
+    }
+
+    /// In‑place intersection (bitwise AND) with another filter.
+    ///
+    /// Both filters must have identical `m` and `k` parameters.
+    pub fn intersect_inplace(&mut self, other: &Self) {
+        assert_eq!(self.bits.len_bits(), other.bits.len_bits(), "m mismatch for intersection");
+        assert_eq!(self.k, other.k, "k mismatch for intersection");
+        self.bits.and_with(&other.bits);
+    }
+
+    /// Clear all bits and reset the item counter to zero.
+    pub fn clear(&mut self) {
+        self.bits.clear();
+        self.items = 0;
+    }
+
+    /// Approximate number of times `insert` was called.
+    pub fn approximate_items(&self) -> usize {
+        self.items
+    }
+
+    /// Number of bits (`m = k * slice_bits`).
+    pub fn num_bits(&self) -> usize {
+        self.bits.len_bits()
+    }
+
+    /// Number of hash functions and slices (`k`).
+    pub fn num_hashes(&self) -> u32 {
+        self.k
+    }
+
+    /// Width of each slice in bits.
+    pub fn slice_bits(&self) -> usize {
+        self.slice_bits
+    }
+
+    /// Number of bits set in each slice.
+    pub fn slice_ones(&self) -> Vec<usize> {
+        let words_per_slice = self.slice_bits / 64;
+        self.bits
+            .words_slice()
+            .chunks_exact(words_per_slice)
+            .map(simd::count_ones)
+            .collect()
+    }
+
+    /// Fraction of bits set in each slice.
+    pub fn slice_fill_ratios(&self) -> Vec<f64> {
+        self.slice_ones()
+            .into_iter()
+            .map(|ones| ones as f64 / self.slice_bits as f64)
+            .collect()
+    }
+
+    /// False-positive rate implied by the current slice fills.
+    ///
+    /// Slices are independent, so this is the product of the fill ratios.
+    pub fn current_fpr(&self) -> f64 {
+        self.slice_fill_ratios().into_iter().product()
+    }
+
+    /// Expected false-positive rate at the current item count (`math::partitioned_fpr`).
+    pub fn estimated_fpr(&self) -> f64 {
+        math::partitioned_fpr(self.num_bits(), self.items, self.k)
+    }
+
+    /// Serialize the filter into a byte vector.
+    ///
+    /// Layout of `BloomFilter::to_bytes` plus a tag:
+    ///   words (u64 little‑endian) + m (u64 LE) + k (u32 LE) + b"PBLM"
+    ///
+    /// The bits mean something else than in a plain filter; the tag keeps
+    /// `BloomFilter::from_bytes_hasher` from reading them as one.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let words = self.bits.words_slice();
+        let mut out = Vec::with_capacity(words.len() * 8 + 16);
+        for w in words {
+            out.extend_from_slice(&w.to_le_bytes());
+        }
+        out.extend_from_slice(&(self.num_bits() as u64).to_le_bytes());
+        out.extend_from_slice(&self.k.to_le_bytes());
+        out.extend_from_slice(&TAG);
+        out
+    }
+
+    /// Deserialize from bytes with an explicit hasher builder.
+    ///
+    /// Returns `None` if the data length or internal layout is invalid, if
+    /// `m` is not `k` word-aligned slices, or if the tag is missing (e.g.
+    /// `BloomFilter::to_bytes` data).
+    pub fn from_bytes_hasher(data: &[u8], hasher_builder: S) -> Option<Self> {
+        let data = data.strip_suffix(&TAG)?;
+        if data.len() < 12 { return None; }
+        let meta = data.len() - 12;
+        let m = u64::from_le_bytes(data[meta..meta + 8].try_into().unwrap()) as usize;
+        let k = u32::from_le_bytes(data[meta + 8..].try_into().unwrap());
+        if k == 0 || m == 0 || !m.is_multiple_of(k as usize * 64) || meta != m / 8 { return None; }
+
+        let words = data[..meta]
+            .chunks_exact(8)
+            .map(|c| u64::from_le_bytes(c.try_into().unwrap()))
+            .collect();
+        Some(Self {
+            bits: BitSet::from_words(m, words),
+            slice_bits: m / k as usize,
+            k,
+            items: 0,
+            hasher_builder,
+        })
+    }
+}
@@ -0,0 +1,495 @@
+use core::hash::{BuildHasher, Hash};
+
+use std::fmt;
+
+use crate::math;
+
+/// Load factor `new_for_capacity` sizes for; clusters grow quickly beyond it.
+pub const TARGET_LOAD: f64 = 0.75;
+
+const OCCUPIED: u64 = 1;
+const CONTINUATION: u64 = 2;
+const SHIFTED: u64 = 4;
+const META_BITS: u32 = 3;
+
+/// Error returned by quotient filter operations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuotientError {
+    /// Every slot is in use; call `grow` first.
+    Full,
+    /// The filter cannot double again: only one remainder bit is left.
+    RemainderExhausted,
+    /// The filters store fingerprints of different lengths (`q + r`).
+    Incompatible,
+}
+
+impl fmt::Display for QuotientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuotientError::Full => write!(f, "quotient filter is full"),
+            QuotientError::RemainderExhausted => write!(f, "no remainder bits left to move into the quotient"),
+            QuotientError::Incompatible => write!(f, "quotient filters have different fingerprint lengths"),
+        }
+    }
+}
+
+impl std::error::Error for QuotientError {}
+
+/// Quotient filter (Bender et al., 2012).
+///
+/// Each item is reduced to a `q + r` bit fingerprint: the top `q` bits pick
+/// a canonical slot and the low `r` bits are stored there (or shifted to the
+/// right of it) alongside the `is_occupied`, `is_continuation` and
+/// `is_shifted` metadata bits. Because the full fingerprint can be rebuilt
+/// from the table, the filter can be resized and merged without the keys.
+/// Runs are multisets: every insert stores its own entry, so removing one
+/// item never drops another that shares its fingerprint.
+#[derive(Clone)]
+pub struct QuotientFilter<S = std::collections::hash_map::RandomState> {
+    table: Vec<u64>,
+    qbits: u32,
+    rbits: u32,
+    len: usize,
+    hasher_builder: S,
+}
+
+impl<S> fmt::Debug for QuotientFilter<S>
+where
+    S: BuildHasher + Clone,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("QuotientFilter")
+            .field("q", &self.qbits)
+            .field("r", &self.rbits)
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+impl QuotientFilter<std::collections::hash_map::RandomState> {
+    /// convenience constructor with `2^q` slots of `r`-bit remainders.
+    pub fn new(q: u32, r: u32) -> Self {
+        Self::with_hasher(q, r, std::collections::hash_map::RandomState::new())
+    }
+
+    /// convenience constructor from capacity and false-positive rate with default hasher.
+    pub fn new_for_capacity(n: usize, p: f64) -> Self {
+        Self::for_capacity_with_hasher(n, p, std::collections::hash_map::RandomState::new())
+    }
+}
+
+impl<S> QuotientFilter<S>
+where
+    S: BuildHasher + Clone,
+{
+    /// create with explicit hasher builder, `2^q` slots and `r`-bit remainders.
+    ///
+    /// Requires `q >= 1`, `1 <= r <= 61` and `q + r <= 64`.
+    pub fn with_hasher(q: u32, r: u32, hasher_builder: S) -> Self {
+        assert!(q >= 1 && r >= 1 && q + r <= 64, "need q >= 1, r >= 1 and q + r <= 64");
+        assert!(r + META_BITS <= 64, "r must be <= 61");
+        let slot_bits = (r + META_BITS) as usize;
+        Self {
+            table: vec![0u64; (slot_bits << q).div_ceil(64)],
+            qbits: q,
+            rbits: r,
+            len: 0,
+            hasher_builder,
+        }
+    }
+
+    /// create sized for `n` items at `TARGET_LOAD` with remainders from `math::quotient_remainder_bits`.
+    pub fn for_capacity_with_hasher(n: usize, p: f64, hasher_builder: S) -> Self {
+        assert!(n > 0, "n must be > 0");
+        let slots = (n as f64 / TARGET_LOAD).ceil() as usize;
+        let q = slots.next_power_of_two().trailing_zeros().max(1);
+        let r = math::quotient_remainder_bits(p).min(64 - q).min(64 - META_BITS);
+        Self::with_hasher(q, r, hasher_builder)
+    }
+
+    fn slots(&self) -> usize {
+        1usize << self.qbits
+    }
+
+    fn slot_bits(&self) -> u32 {
+        self.rbits + META_BITS
+    }
+
+    fn get(&self, idx: usize) -> u64 {
+        let width = self.slot_bits() as usize;
+        let off = idx * width;
+        let (w, shift) = (off / 64, off % 64);
+        let mut v = self.table[w] >> shift;
+        if shift + width > 64 {
+            v |= self.table[w + 1] << (64 - shift);
+        }
+        v & self.slot_mask()
+    }
+
+    fn set(&mut self, idx: usize, elt: u64) {
+        let width = self.slot_bits() as usize;
+        let off = idx * width;
+        let (w, shift) = (off / 64, off % 64);
+        let mask = self.slot_mask();
+        self.table[w] = (self.table[w] & !(mask << shift)) | (elt << shift);
+        if shift + width > 64 {
+            let spill = 64 - shift;
+            self.table[w + 1] = (self.table[w + 1] & !(mask >> spill)) | (elt >> spill);
+        }
+    }
+
+    fn slot_mask(&self) -> u64 {
+        u64::MAX >> (64 - self.slot_bits())
+    }
+
+    fn incr(&self, idx: usize) -> usize {
+        (idx + 1) & (self.slots() - 1)
+    }
+
+    fn decr(&self, idx: usize) -> usize {
+        idx.wrapping_sub(1) & (self.slots() - 1)
+    }
+
+    fn fingerprint<T: Hash>(&self, item: &T) -> u64 {
+        let h = self.hasher_builder.hash_one(item);
+        h >> (64 - self.qbits - self.rbits)
+    }
+
+    fn split(&self, fingerprint: u64) -> (usize, u64) {
+        ((fingerprint >> self.rbits) as usize, fingerprint & ((1u64 << self.rbits) - 1))
+    }
+
+    /// Start of the run belonging to canonical slot `fq`.
+    fn find_run_index(&self, fq: usize) -> usize {
+        // walk back to the start of the cluster
+        let mut b = fq;
+        while is_shifted(self.get(b)) {
+            b = self.decr(b);
+        }
+        // walk forward, pairing each occupied quotient with its run
+        let mut s = b;
+        while b != fq {
+            loop {
+                s = self.incr(s);
+                if !is_continuation(self.get(s)) { break; }
+            }
+            loop {
+                b = self.incr(b);
+                if is_occupied(self.get(b)) { break; }
+            }
+        }
+        s
+    }
+
+    /// Insert `elt` at `s`, shifting the rest of the cluster right by one slot.
+    /// `is_occupied` bits stay with their slots.
+    fn insert_into(&mut self, mut s: usize, elt: u64) {
+        let mut curr = elt;
+        loop {
+            let mut prev = self.get(s);
+            let empty = is_empty(prev);
+            if !empty {
+                prev |= SHIFTED;
+                if is_occupied(prev) {
+                    curr |= OCCUPIED;
+                    prev &= !OCCUPIED;
+                }
+            }
+            self.set(s, curr);
+            curr = prev;
+            s = self.incr(s);
+            if empty { break; }
+        }
+    }
+
+    /// Slot holding remainder `fr` in the run of canonical slot `fq`, if stored.
+    fn find_remainder(&self, fq: usize, fr: u64) -> Option<usize> {
+        if !is_occupied(self.get(fq)) {
+            return None;
+        }
+        let mut s = self.find_run_index(fq);
+        loop {
+            let rem = remainder(self.get(s));
+            if rem == fr {
+                return Some(s);
+            } else if rem > fr {
+                return None;
+            }
+            s = self.incr(s);
+            if !is_continuation(self.get(s)) { return None; }
+        }
+    }
+
+    fn insert_fingerprint(&mut self, fingerprint: u64) -> Result<(), QuotientError> {
+        if self.len >= self.slots() {
+            return Err(QuotientError::Full);
+        }
+        let (fq, fr) = self.split(fingerprint);
+        let t_fq = self.get(fq);
+        let mut entry = fr << META_BITS;
+
+        if is_empty(t_fq) {
+            self.set(fq, entry | OCCUPIED);
+            self.len += 1;
+            return Ok(());
+        }
+        if !is_occupied(t_fq) {
+            self.set(fq, t_fq | OCCUPIED);
+        }
+
+        let start = self.find_run_index(fq);
+        let mut s = start;
+        if is_occupied(t_fq) {
+            // keep runs sorted by remainder, duplicates after their equals
+            loop {
+                if remainder(self.get(s)) > fr {
+                    break;
+                }
+                s = self.incr(s);
+                if !is_continuation(self.get(s)) { break; }
+            }
+            if s == start {
+                let old_head = self.get(start);
+                self.set(start, old_head | CONTINUATION);
+            } else {
+                entry |= CONTINUATION;
+            }
+        }
+        if s != fq {
+            entry |= SHIFTED;
+        }
+        self.insert_into(s, entry);
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Insert an item.
+    ///
+    /// Every call stores one entry, even when the fingerprint is already
+    /// present (another item with the same fingerprint, or the same item
+    /// again), so each inserted copy can be removed on its own. Returns
+    /// `Err(QuotientError::Full)` when every slot is taken.
+    pub fn insert<T: Hash>(&mut self, item: &T) -> Result<(), QuotientError> {
+        let fp = self.fingerprint(item);
+        self.insert_fingerprint(fp)
+    }
+
+    /// Test whether an item is *probably* in the set.
+    pub fn contains<T: Hash>(&self, item: &T) -> bool {
+        let (fq, fr) = self.split(self.fingerprint(item));
+        self.find_remainder(fq, fr).is_some()
+    }
+
+    /// Remove one copy of an item's fingerprint. Returns `true` if it was present.
+    ///
+    /// Only remove items that were actually inserted: removing a false
+    /// positive deletes another item's fingerprint. Other copies of the
+    /// fingerprint, from colliding items or repeated inserts, stay.
+    pub fn remove<T: Hash>(&mut self, item: &T) -> bool {
+        let (fq, fr) = self.split(self.fingerprint(item));
+        let s = match self.find_remainder(fq, fr) {
+            Some(s) => s,
+            None => return false,
+        };
+        let mut t_fq = self.get(fq);
+
+        let kill = if s == fq { t_fq } else { self.get(s) };
+        let replace_run_start = is_run_start(kill);
+
+        // deleting the only entry of a run clears the canonical slot's occupied bit
+        if replace_run_start && !is_continuation(self.get(self.incr(s))) {
+            t_fq &= !OCCUPIED;
+            self.set(fq, t_fq);
+        }
+
+        self.delete_entry(s, fq);
+
+        if replace_run_start {
+            let next = self.get(s);
+            let mut updated = next;
+            if is_continuation(next) {
+                // the new head of the run is no longer a continuation
+                updated &= !CONTINUATION;
+            }
+            if s == fq && is_run_start(updated) {
+                // and if it now sits in its canonical slot it isn't shifted
+                updated &= !SHIFTED;
+            }
+            if updated != next {
+                self.set(s, updated);
+            }
+        }
+
+        self.len -= 1;
+        true
+    }
+
+    /// Remove the entry at `s` (whose run belongs to `quot`), shifting the
+    /// rest of the cluster left and fixing up metadata.
+    fn delete_entry(&mut self, mut s: usize, mut quot: usize) {
+        let orig = s;
+        let mut curr = self.get(s);
+        let mut sp = self.incr(s);
+        loop {
+            let next = self.get(sp);
+            let curr_occupied = is_occupied(curr);
+            if is_empty(next) || is_cluster_start(next) || sp == orig {
+                self.set(s, curr & OCCUPIED);
+                return;
+            }
+
+            // entries sliding back into their canonical slot lose is_shifted
+            let mut updated = next;
+            if is_run_start(next) {
+                loop {
+                    quot = self.incr(quot);
+                    if is_occupied(self.get(quot)) { break; }
+                }
+                if curr_occupied && quot == s {
+                    updated &= !SHIFTED;
+                }
+            }
+            self.set(s, if curr_occupied { updated | OCCUPIED } else { updated & !OCCUPIED });
+            s = sp;
+            sp = self.incr(sp);
+            curr = next;
+        }
+    }
+
+    /// Every stored fingerprint (`q + r` bits), in table order.
+    fn fingerprints(&self) -> Vec<u64> {
+        let mut out = Vec::with_capacity(self.len);
+        if self.len == 0 {
+            return out;
+        }
+        let start = match (0..self.slots()).find(|&i| is_cluster_start(self.get(i))) {
+            Some(i) => i,
+            None => return out,
+        };
+        let mut quotient = start;
+        let mut idx = start;
+        for _ in 0..self.slots() {
+            let elt = self.get(idx);
+            if is_cluster_start(elt) {
+                quotient = idx;
+            } else if is_run_start(elt) {
+                loop {
+                    quotient = self.incr(quotient);
+                    if is_occupied(self.get(quotient)) { break; }
+                }
+            }
+            if !is_empty(elt) {
+                out.push(((quotient as u64) << self.rbits) | remainder(elt));
+            }
+            idx = self.incr(idx);
+        }
+        out
+    }
+
+    /// Double the number of slots by moving one remainder bit into the quotient.
+    ///
+    /// Fingerprints are rebuilt from the table, so the keys are not needed.
+    /// The false-positive rate per occupied slot doubles as `r` shrinks.
+    pub fn grow(&mut self) -> Result<(), QuotientError> {
+        if self.rbits <= 1 {
+            return Err(QuotientError::RemainderExhausted);
+        }
+        let mut bigger = Self::with_hasher(self.qbits + 1, self.rbits - 1, self.hasher_builder.clone());
+        for fp in self.fingerprints() {
+            bigger.insert_fingerprint(fp)?;
+        }
+        *self = bigger;
+        Ok(())
+    }
+
+    /// Merge two filters into a new one holding the entries of both.
+    ///
+    /// Both filters must store fingerprints of the same length (`q + r`) and
+    /// share a hasher; the result grows until the combined entries fit within
+    /// `TARGET_LOAD`.
+    pub fn merge(&self, other: &Self) -> Result<Self, QuotientError> {
+        let fp_bits = self.qbits + self.rbits;
+        if fp_bits != other.qbits + other.rbits {
+            return Err(QuotientError::Incompatible);
+        }
+        let needed = ((self.len + other.len) as f64 / TARGET_LOAD).ceil() as usize;
+        let mut q = self.qbits.max(other.qbits);
+        while (1usize << q) < needed && q + 1 < fp_bits {
+            q += 1;
+        }
+        let mut merged = Self::with_hasher(q, fp_bits - q, self.hasher_builder.clone());
+        for fp in self.fingerprints().into_iter().chain(other.fingerprints()) {
+            merged.insert_fingerprint(fp)?;
+        }
+        Ok(merged)
+    }
+
+    /// Remove all items.
+    pub fn clear(&mut self) {
+        self.table.fill(0);
+        self.len = 0;
+    }
+
+    /// Number of stored entries, duplicates included.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// `true` if no fingerprints are stored.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Number of slots (`2^q`).
+    pub fn capacity(&self) -> usize {
+        self.slots()
+    }
+
+    /// Fraction of slots in use.
+    pub fn load_factor(&self) -> f64 {
+        self.len as f64 / self.slots() as f64
+    }
+
+    /// Quotient bits (`q`).
+    pub fn quotient_bits(&self) -> u32 {
+        self.qbits
+    }
+
+    /// Remainder bits (`r`).
+    pub fn remainder_bits(&self) -> u32 {
+        self.rbits
+    }
+
+    /// Expected false-positive rate at the current load (`math::quotient_fpr`).
+    pub fn estimated_fpr(&self) -> f64 {
+        math::quotient_fpr(self.load_factor(), self.rbits)
+    }
+}
+
+fn is_occupied(elt: u64) -> bool {
+    elt & OCCUPIED != 0
+}
+
+fn is_continuation(elt: u64) -> bool {
+    elt & CONTINUATION != 0
+}
+
+fn is_shifted(elt: u64) -> bool {
+    elt & SHIFTED != 0
+}
+
+fn is_empty(elt: u64) -> bool {
+    elt & (OCCUPIED | CONTINUATION | SHIFTED) == 0
+}
+
+fn is_cluster_start(elt: u64) -> bool {
+    is_occupied(elt) && !is_continuation(elt) && !is_shifted(elt)
+}
+
+fn is_run_start(elt: u64) -> bool {
+    !is_continuation(elt) && (is_occupied(elt) || is_shifted(elt))
+}
+
+fn remainder(elt: u64) -> u64 {
+    elt >> META_BITS
+}
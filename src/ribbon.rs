@@ -0,0 +1,309 @@
+use core::hash::{BuildHasher, Hash};
+
+use std::fmt;
+
+use crate::hashing::{murmur64, splitmix64};
+use crate::{bitset::BitSet, math};
+
+/// Construction attempts (each with a fresh seed) before giving up.
+const MAX_ATTEMPTS: usize = 16;
+/// Failed attempts after which the number of slots grows by 2%.
+const ATTEMPTS_PER_SIZE: usize = 2;
+/// Upper bound on result bits (columns) per slot.
+const MAX_RESULT_BITS: u32 = 32;
+
+/// Error returned when a ribbon filter cannot be built.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RibbonError {
+    /// The banded system had no solution for any seed tried.
+    ///
+    /// Only happens when distinct keys collide on their full 64-bit hash.
+    ConstructionFailed,
+}
+
+impl fmt::Display for RibbonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RibbonError::ConstructionFailed => write!(f, "ribbon construction failed after {} attempts", MAX_ATTEMPTS),
+        }
+    }
+}
+
+impl std::error::Error for RibbonError {}
+
+/// Static standard Ribbon filter (Dillinger & Walzer, 2021).
+///
+/// Each key yields a start slot, a 64-bit coefficient row and a fingerprint;
+/// construction solves the resulting banded linear system over GF(2) and
+/// stores the solution. A query recomputes the row and checks that its dot
+/// product with the solution equals the fingerprint.
+///
+/// The solution lives in a `BitSet`, interleaved in 64-slot blocks with one
+/// word per result column. Blocks from `upper_start` onward keep one extra column,
+/// which lets the filter hit fractional bits/key, i.e. arbitrary FPRs.
+#[derive(Clone)]
+pub struct RibbonFilter<S = std::collections::hash_map::RandomState> {
+    solution: BitSet,
+    num_slots: usize,
+    result_bits: u32,
+    upper_start: usize,
+    seed: u64,
+    len: usize,
+    hasher_builder: S,
+}
+
+impl<S> fmt::Debug for RibbonFilter<S>
+where
+    S: BuildHasher + Clone,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RibbonFilter")
+            .field("slots", &self.num_slots)
+            .field("result_bits", &self.result_bits)
+            .field("upper_start", &self.upper_start)
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+impl RibbonFilter<std::collections::hash_map::RandomState> {
+    /// convenience constructor using default hasher builder.
+    pub fn from_keys<T: Hash>(keys: impl IntoIterator<Item = T>, p: f64) -> Result<Self, RibbonError> {
+        Self::from_keys_with_hasher(keys, p, std::collections::hash_map::RandomState::new())
+    }
+}
+
+/// Columns `r` for every block and the fraction of blocks that get `r + 1`
+/// (`math::ribbon_result_bits` split into whole and fractional parts).
+fn columns_for(p: f64) -> (u32, f64) {
+    let bits = math::ribbon_result_bits(p);
+    if bits >= MAX_RESULT_BITS as f64 {
+        return (MAX_RESULT_BITS, 0.0);
+    }
+    (bits.floor() as u32, bits.fract())
+}
+
+/// A key's equation for a given seed: (start slot, coefficient row, fingerprint).
+#[inline]
+fn equation(key_hash: u64, seed: u64, num_slots: usize) -> (usize, u64, u64) {
+    let h = murmur64(key_hash ^ seed);
+    let start = ((h as u128 * (num_slots - 63) as u128) >> 64) as usize;
+    let coeff = murmur64(h ^ 0x9e37_79b9_7f4a_7c15) | 1;
+    let fp = murmur64(h ^ 0xc2b2_ae3d_27d4_eb4f);
+    (start, coeff, fp)
+}
+
+impl<S> RibbonFilter<S>
+where
+    S: BuildHasher + Clone,
+{
+    /// Build from a key iterator with an explicit hasher builder, targeting false-positive rate `p`.
+    ///
+    /// Duplicate keys are fine. Returns `Err(RibbonError::ConstructionFailed)`
+    /// if no seed yields a solvable system.
+    pub fn from_keys_with_hasher<T: Hash>(keys: impl IntoIterator<Item = T>, p: f64, hasher_builder: S) -> Result<Self, RibbonError> {
+        let key_hashes: Vec<u64> = keys.into_iter().map(|k| hasher_builder.hash_one(&k)).collect();
+        let (r, upper_fraction) = columns_for(p);
+        let mut num_slots = math::ribbon_num_slots(key_hashes.len());
+        let mut rng = 0x5851_f42d_4c95_7f2du64;
+
+        for attempt in 0..MAX_ATTEMPTS {
+            if attempt > 0 && attempt % ATTEMPTS_PER_SIZE == 0 {
+                num_slots = (num_slots + num_slots / 50).div_ceil(64) * 64;
+            }
+            let seed = splitmix64(&mut rng);
+            let num_blocks = num_slots / 64;
+            let upper_blocks = (upper_fraction * num_blocks as f64).round() as usize;
+            let mut filter = Self {
+                solution: BitSet::new(0),
+                num_slots,
+                result_bits: r,
+                upper_start: num_blocks - upper_blocks,
+                seed,
+                len: key_hashes.len(),
+                hasher_builder: hasher_builder.clone(),
+            };
+            if filter.solve(&key_hashes) {
+                return Ok(filter);
+            }
+        }
+        Err(RibbonError::ConstructionFailed)
+    }
+
+    fn columns(&self, block: usize) -> u32 {
+        self.result_bits + (block >= self.upper_start) as u32
+    }
+
+    fn block_offset(&self, block: usize) -> usize {
+        block * self.result_bits as usize + block.saturating_sub(self.upper_start)
+    }
+
+    /// Band the equations with on-the-fly Gaussian elimination, then
+    /// back-substitute into the interleaved solution. Returns `false` if
+    /// two equations contradict each other.
+    fn solve(&mut self, key_hashes: &[u64]) -> bool {
+        let m = self.num_slots;
+        let max_cols = self.columns(m / 64 - 1);
+        let fp_mask = (1u64 << max_cols) - 1;
+        let mut coeffs = vec![0u64; m];
+        let mut results = vec![0u64; m];
+
+        for &kh in key_hashes {
+            let (mut s, mut c, fp) = equation(kh, self.seed, m);
+            let mut fp = fp & fp_mask;
+            loop {
+                if coeffs[s] == 0 {
+                    coeffs[s] = c;
+                    results[s] = fp;
+                    break;
+                }
+                c ^= coeffs[s];
+                fp ^= results[s];
+                if c == 0 {
+                    if fp == 0 {
+                        break; // duplicate of an earlier equation
+                    }
+                    return false;
+                }
+                let tz = c.trailing_zeros();
+                s += tz as usize;
+                c >>= tz;
+            }
+        }
+
+        // bit k of window[j] holds solution bit j of row i + k
+        let mut words = vec![0u64; self.block_offset(m / 64)];
+        let mut window = vec![0u64; max_cols as usize];
+        for i in (0..m).rev() {
+            let block = i / 64;
+            let cols = self.columns(block) as usize;
+            let offset = self.block_offset(block);
+            for (j, win) in window.iter_mut().enumerate() {
+                *win <<= 1;
+                let bit = if coeffs[i] != 0 {
+                    ((coeffs[i] & *win).count_ones() as u64 & 1) ^ ((results[i] >> j) & 1)
+                } else {
+                    // free variable: any value works, random keeps the FPR honest
+                    (murmur64(self.seed ^ i as u64) >> j) & 1
+                };
+                *win |= bit;
+                if j < cols {
+                    words[offset + j] |= bit << (i % 64);
+                }
+            }
+        }
+        self.solution = BitSet::from_words(words.len() * 64, words);
+        true
+    }
+
+    /// Test whether an item is *probably* in the set.
+    pub fn contains<T: Hash>(&self, item: &T) -> bool {
+        let (s, c, fp) = equation(self.hasher_builder.hash_one(item), self.seed, self.num_slots);
+        let (block, shift) = (s / 64, s % 64);
+        let offset = self.block_offset(block);
+        let next = self.block_offset(block + 1);
+        let words = self.solution.words_slice();
+        for j in 0..self.columns(block) as usize {
+            let mut z = words[offset + j] >> shift;
+            if shift > 0 {
+                z |= words[next + j] << (64 - shift);
+            }
+            if (c & z).count_ones() as u64 & 1 != (fp >> j) & 1 {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Number of keys the filter was built from (including duplicates).
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// `true` if built from an empty key set.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Number of solution rows.
+    pub fn num_slots(&self) -> usize {
+        self.num_slots
+    }
+
+    /// Average result bits per slot (fractional when some blocks have an extra column).
+    pub fn result_bits(&self) -> f64 {
+        self.solution.len_bits() as f64 / self.num_slots as f64
+    }
+
+    /// Expected false-positive rate given the column layout.
+    pub fn estimated_fpr(&self) -> f64 {
+        let blocks = self.num_slots / 64;
+        let upper = (blocks - self.upper_start) as f64 / blocks as f64;
+        let base = 2f64.powi(-(self.result_bits as i32));
+        (1.0 - upper) * base + upper * base / 2.0
+    }
+
+    /// Size of the solution in bytes.
+    pub fn size_in_bytes(&self) -> usize {
+        self.solution.len_bits() / 8
+    }
+
+    /// Storage cost per key in bits.
+    pub fn bits_per_entry(&self) -> f64 {
+        (self.size_in_bytes() * 8) as f64 / self.len.max(1) as f64
+    }
+
+    /// Serialize the filter into a byte vector.
+    ///
+    /// Layout:
+    ///   words (u64 LE) + slots (u64 LE) + upper start block (u64 LE)
+    ///   + seed (u64 LE) + keys (u64 LE) + result bits (u32 LE)
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let words = self.solution.words_slice();
+        let mut out = Vec::with_capacity(words.len() * 8 + 36);
+        for w in words {
+            out.extend_from_slice(&w.to_le_bytes());
+        }
+        out.extend_from_slice(&(self.num_slots as u64).to_le_bytes());
+        out.extend_from_slice(&(self.upper_start as u64).to_le_bytes());
+        out.extend_from_slice(&self.seed.to_le_bytes());
+        out.extend_from_slice(&(self.len as u64).to_le_bytes());
+        out.extend_from_slice(&self.result_bits.to_le_bytes());
+        out
+    }
+
+    /// Deserialize from bytes with the hasher builder used to build the filter.
+    ///
+    /// Returns `None` if the data length or internal layout is invalid.
+    pub fn from_bytes_hasher(data: &[u8], hasher_builder: S) -> Option<Self> {
+        if data.len() < 36 { return None; }
+        let meta = data.len() - 36;
+        let u64_at = |at: usize| u64::from_le_bytes(data[at..at + 8].try_into().unwrap());
+        let num_slots = u64_at(meta) as usize;
+        let upper_start = u64_at(meta + 8) as usize;
+        let seed = u64_at(meta + 16);
+        let len = u64_at(meta + 24) as usize;
+        let result_bits = u32::from_le_bytes(data[meta + 32..].try_into().unwrap());
+        if num_slots < 128 || !num_slots.is_multiple_of(64) || upper_start > num_slots / 64
+            || result_bits == 0 || result_bits > MAX_RESULT_BITS {
+            return None;
+        }
+
+        let mut filter = Self {
+            solution: BitSet::new(0),
+            num_slots,
+            result_bits,
+            upper_start,
+            seed,
+            len,
+            hasher_builder,
+        };
+        if meta != filter.block_offset(num_slots / 64) * 8 { return None; }
+        let words = data[..meta]
+            .chunks_exact(8)
+            .map(|c| u64::from_le_bytes(c.try_into().unwrap()))
+            .collect();
+        filter.solution = BitSet::from_words(meta * 8, words);
+        Some(filter)
+    }
+}
@@ -0,0 +1,247 @@
+use core::hash::{BuildHasher, Hash};
+
+use std::fmt;
+#[cfg(feature = "serde")] use serde::{Deserialize, Serializer, Deserializer, ser::SerializeStruct};
+#[cfg(feature = "serde")] use crate::seeded::PersistentHasher;
+
+use crate::{bloom::BloomFilter, math};
+
+/// Default capacity multiplier between consecutive stages.
+pub const DEFAULT_GROWTH: usize = 2;
+/// Default error tightening ratio between consecutive stages.
+pub const DEFAULT_TIGHTENING: f64 = 0.85;
+
+/// Scalable bloom filter (Almeida et al., 2007).
+///
+/// Chains `BloomFilter` stages: stage `i` is sized for
+/// `initial_capacity * growth^i` items at error `p * (1 - r) * r^i`, so the
+/// compound false-positive rate stays below the target `p` however many
+/// stages are added. New items always go into the newest stage.
+#[derive(Clone)]
+pub struct ScalableBloomFilter<S = std::collections::hash_map::RandomState> {
+    stages: Vec<BloomFilter<S>>,
+    initial_capacity: usize,
+    fp_rate: f64,
+    growth: usize,
+    tightening: f64,
+    hasher_builder: S,
+}
+
+impl<S> fmt::Debug for ScalableBloomFilter<S>
+where
+    S: BuildHasher + Clone,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ScalableBloomFilter")
+            .field("stages", &self.stages)
+            .field("initial_capacity", &self.initial_capacity)
+            .field("fp_rate", &self.fp_rate)
+            .field("growth", &self.growth)
+            .field("tightening", &self.tightening)
+            .finish()
+    }
+}
+
+impl ScalableBloomFilter<std::collections::hash_map::RandomState> {
+    /// convenience constructor using default hasher builder and growth parameters.
+    pub fn new(initial_capacity: usize, p: f64) -> Self {
+        Self::with_hasher(initial_capacity, p, std::collections::hash_map::RandomState::new())
+    }
+}
+
+impl<S> ScalableBloomFilter<S>
+where
+    S: BuildHasher + Clone,
+{
+    /// create with explicit hasher builder and default growth parameters.
+    pub fn with_hasher(initial_capacity: usize, p: f64, hasher_builder: S) -> Self {
+        Self::with_growth(initial_capacity, p, DEFAULT_GROWTH, DEFAULT_TIGHTENING, hasher_builder)
+    }
+
+    /// create with explicit growth factor `s >= 1` and tightening ratio `r` in `(0,1)`.
+    pub fn with_growth(initial_capacity: usize, p: f64, growth: usize, tightening: f64, hasher_builder: S) -> Self {
+        assert!(initial_capacity > 0, "initial_capacity must be > 0");
+        assert!(p > 0.0 && p < 1.0, "p must be in (0,1)");
+        assert!(growth >= 1, "growth must be >= 1");
+        assert!(tightening > 0.0 && tightening < 1.0, "tightening must be in (0,1)");
+        let mut filter = Self {
+            stages: Vec::new(),
+            initial_capacity,
+            fp_rate: p,
+            growth,
+            tightening,
+            hasher_builder,
+        };
+        filter.push_stage();
+        filter
+    }
+
+    /// Design capacity of stage `i`.
+    fn stage_capacity(&self, i: usize) -> usize {
+        let factor = self.growth.saturating_pow(i as u32);
+        self.initial_capacity.saturating_mul(factor)
+    }
+
+    /// Target error rate of stage `i`.
+    fn stage_fp_rate(&self, i: usize) -> f64 {
+        self.fp_rate * (1.0 - self.tightening) * self.tightening.powi(i as i32)
+    }
+
+    fn push_stage(&mut self) {
+        let i = self.stages.len();
+        let n = self.stage_capacity(i);
+        let p = self.stage_fp_rate(i).max(f64::MIN_POSITIVE);
+        let m = math::optimal_m(n, p);
+        let k = math::optimal_k(m, n);
+        self.stages.push(BloomFilter::with_hasher(m, k, self.hasher_builder.clone()));
+    }
+
+    /// Insert an item into the newest stage, adding a stage first if it is full.
+    pub fn insert<T: Hash>(&mut self, item: &T) {
+        let last = self.stages.len() - 1;
+        if self.stages[last].approximate_items() >= self.stage_capacity(last) {
+            self.push_stage();
+        }
+        self.stages.last_mut().expect("at least one stage").insert(item);
+    }
+
+    /// Test whether an item is *probably* in any stage.
+    pub fn contains<T: Hash>(&self, item: &T) -> bool {
+        // newer stages are larger and hold more items, so check them first
+        self.stages.iter().rev().any(|stage| stage.contains(item))
+    }
+
+    /// Drop all but the first stage and clear it.
+    pub fn clear(&mut self) {
+        self.stages.truncate(1);
+        self.stages[0].clear();
+    }
+
+    /// Approximate number of times `insert` was called across all stages.
+    pub fn approximate_items(&self) -> usize {
+        self.stages.iter().map(|s| s.approximate_items()).sum()
+    }
+
+    /// Number of stages currently allocated.
+    pub fn num_stages(&self) -> usize {
+        self.stages.len()
+    }
+
+    /// The stages, oldest first.
+    pub fn stages(&self) -> &[BloomFilter<S>] {
+        &self.stages
+    }
+
+    /// Total design capacity of the allocated stages.
+    pub fn capacity(&self) -> usize {
+        (0..self.stages.len()).map(|i| self.stage_capacity(i)).fold(0, usize::saturating_add)
+    }
+
+    /// Total number of bits across all stages.
+    pub fn num_bits(&self) -> usize {
+        self.stages.iter().map(|s| s.num_bits()).sum()
+    }
+
+    /// Upper bound on the compound false-positive rate of the allocated stages.
+    ///
+    /// `1 - prod(1 - p_i)`; always below the target `p`.
+    pub fn error_bound(&self) -> f64 {
+        let miss: f64 = (0..self.stages.len()).map(|i| 1.0 - self.stage_fp_rate(i)).product();
+        1.0 - miss
+    }
+
+    /// Serialize the whole chain into a byte vector.
+    ///
+    /// Layout (all little‑endian):
+    ///   initial_capacity (u64) + p (f64) + growth (u64) + r (f64) + stage count (u32),
+    ///   then per stage: items (u64) + length (u64) + `BloomFilter::to_bytes`
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(36 + self.num_bits() / 8 + self.stages.len() * 28);
+        out.extend_from_slice(&(self.initial_capacity as u64).to_le_bytes());
+        out.extend_from_slice(&self.fp_rate.to_le_bytes());
+        out.extend_from_slice(&(self.growth as u64).to_le_bytes());
+        out.extend_from_slice(&self.tightening.to_le_bytes());
+        out.extend_from_slice(&(self.stages.len() as u32).to_le_bytes());
+        for stage in &self.stages {
+            let bytes = stage.to_bytes();
+            out.extend_from_slice(&(stage.approximate_items() as u64).to_le_bytes());
+            out.extend_from_slice(&(bytes.len() as u64).to_le_bytes());
+            out.extend_from_slice(&bytes);
+        }
+        out
+    }
+
+    /// Deserialize from bytes with an explicit hasher builder.
+    ///
+    /// Returns `None` if the data length or internal layout is invalid.
+    pub fn from_bytes_hasher(data: &[u8], hasher_builder: S) -> Option<Self> {
+        let mut pos = 0usize;
+        let mut take = |n: usize| -> Option<&[u8]> {
+            let slice = data.get(pos..pos.checked_add(n)?)?;
+            pos += n;
+            Some(slice)
+        };
+        let u64_at = |b: &[u8]| u64::from_le_bytes(b.try_into().unwrap());
+
+        let initial_capacity = u64_at(take(8)?) as usize;
+        let fp_rate = f64::from_bits(u64_at(take(8)?));
+        let growth = u64_at(take(8)?) as usize;
+        let tightening = f64::from_bits(u64_at(take(8)?));
+        let count = u32::from_le_bytes(take(4)?.try_into().unwrap()) as usize;
+        if initial_capacity == 0 || growth == 0 || count == 0
+            || !(fp_rate > 0.0 && fp_rate < 1.0)
+            || !(tightening > 0.0 && tightening < 1.0) {
+            return None;
+        }
+
+        let mut stages = Vec::with_capacity(count.min(64));
+        for _ in 0..count {
+            let items = u64_at(take(8)?) as usize;
+            let len = u64_at(take(8)?) as usize;
+            let mut stage = BloomFilter::from_bytes_hasher(take(len)?, hasher_builder.clone())?;
+            stage.set_approximate_items(items);
+            stages.push(stage);
+        }
+        if pos != data.len() { return None; }
+
+        Some(Self { stages, initial_capacity, fp_rate, growth, tightening, hasher_builder })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<S> serde::Serialize for ScalableBloomFilter<S>
+where S: PersistentHasher {
+    fn serialize<Se: Serializer>(&self, serializer: Se) -> Result<Se::Ok, Se::Error> {
+        let mut st = serializer.serialize_struct("ScalableBloomFilter", 5)?;
+        st.serialize_field("initial_capacity", &self.initial_capacity)?;
+        st.serialize_field("fp_rate", &self.fp_rate)?;
+        st.serialize_field("growth", &self.growth)?;
+        st.serialize_field("tightening", &self.tightening)?;
+        st.serialize_field("stages", &self.stages)?;
+        st.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, S> serde::Deserialize<'de> for ScalableBloomFilter<S>
+where S: PersistentHasher {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(bound(deserialize = "BloomFilter<S>: Deserialize<'de>"))]
+        struct SBFHelper<S> { initial_capacity: usize, fp_rate: f64, growth: usize, tightening: f64, stages: Vec<BloomFilter<S>> }
+        let helper = SBFHelper::<S>::deserialize(deserializer)?;
+        if helper.stages.is_empty() || helper.initial_capacity == 0 || helper.growth == 0 {
+            return Err(serde::de::Error::custom("invalid scalable filter parameters"));
+        }
+        // stages restore their own seeded hasher; new stages share it
+        let hasher_builder = helper.stages[0].hasher_builder().clone();
+        Ok(Self {
+            stages: helper.stages,
+            initial_capacity: helper.initial_capacity,
+            fp_rate: helper.fp_rate,
+            growth: helper.growth,
+            tightening: helper.tightening,
+            hasher_builder,
+        })
+    }
+}
@@ -0,0 +1,376 @@
+use core::hash::{BuildHasher, Hash, Hasher};
+
+use crate::hashing::{splitmix64, Hash128Builder};
+
+/// Hasher builder whose identity can be stored next to a filter and rebuilt later.
+///
+/// Every `BuildHasher + Clone + Default` (e.g. `RandomState`) implements this
+/// with `ID == 0`: it carries no recordable state and is rebuilt with
+/// `Default::default()`, which for `RandomState` is a fresh random key. Use one
+/// of the seeded builders in this module for filters that must be reloaded.
+pub trait PersistentHasher: BuildHasher + Clone {
+    /// Algorithm id stored with the filter. `0` means "opaque"; `1..=127` are
+    /// reserved for this crate, `128..=255` are free for user builders.
+    const ID: u8;
+
+    /// Seed to store with the filter.
+    fn seed(&self) -> u64;
+
+    /// Rebuild the builder from a stored seed.
+    fn from_seed(seed: u64) -> Self;
+
+    /// The builder's `Hash128Builder` digest, if it has one.
+    ///
+    /// Readers use it to reload filters written with single-pass hashing
+    /// (see `BloomFilter::with_hasher_128`). Builders implementing
+    /// `Hash128Builder` should forward to `hash_one_128`; the default, `None`,
+    /// makes such files fail to load with `FormatError::FingerprintMismatch`.
+    fn digest128<T: Hash + ?Sized>(&self, _item: &T) -> Option<u128> {
+        None
+    }
+}
+
+impl<S> PersistentHasher for S
+where
+    S: BuildHasher + Clone + Default,
+{
+    const ID: u8 = 0;
+
+    fn seed(&self) -> u64 {
+        0
+    }
+
+    fn from_seed(_seed: u64) -> Self {
+        S::default()
+    }
+}
+
+/// Writes integers as fixed-width little-endian bytes (`usize`/`isize` as 64
+/// bits) so `Hash` impls feed the same bytes on every platform.
+macro_rules! portable_int_writes {
+    () => {
+        fn write_u8(&mut self, i: u8) {
+            self.write(&[i]);
+        }
+        fn write_u16(&mut self, i: u16) {
+            self.write(&i.to_le_bytes());
+        }
+        fn write_u32(&mut self, i: u32) {
+            self.write(&i.to_le_bytes());
+        }
+        fn write_u64(&mut self, i: u64) {
+            self.write(&i.to_le_bytes());
+        }
+        fn write_u128(&mut self, i: u128) {
+            self.write(&i.to_le_bytes());
+        }
+        fn write_usize(&mut self, i: usize) {
+            self.write(&(i as u64).to_le_bytes());
+        }
+        fn write_isize(&mut self, i: isize) {
+            self.write(&(i as i64).to_le_bytes());
+        }
+    };
+}
+
+/// SipHash-1-3 (the algorithm behind std's `DefaultHasher`) with an explicit key.
+///
+/// Unlike std's, this implementation is guaranteed not to change between
+/// releases and feeds integers as little-endian bytes.
+#[derive(Debug, Clone)]
+pub struct SipHasher13 {
+    v0: u64,
+    v1: u64,
+    v2: u64,
+    v3: u64,
+    tail: u64,
+    ntail: usize,
+    length: usize,
+}
+
+impl SipHasher13 {
+    /// Hasher keyed with `(k0, k1)`.
+    pub fn new_with_keys(k0: u64, k1: u64) -> Self {
+        Self {
+            v0: k0 ^ 0x736f_6d65_7073_6575,
+            v1: k1 ^ 0x646f_7261_6e64_6f6d,
+            v2: k0 ^ 0x6c79_6765_6e65_7261,
+            v3: k1 ^ 0x7465_6462_7974_6573,
+            tail: 0,
+            ntail: 0,
+            length: 0,
+        }
+    }
+
+    /// Hasher for the 128-bit variant keyed with `(k0, k1)`; read it with `finish128`.
+    pub fn new_with_keys_128(k0: u64, k1: u64) -> Self {
+        let mut h = Self::new_with_keys(k0, k1);
+        h.v1 ^= 0xee;
+        h
+    }
+
+    /// SipHash-1-3-128 digest (low half first, as in the reference output bytes).
+    ///
+    /// Only meaningful for hashers built with `new_with_keys_128`.
+    pub fn finish128(&self) -> u128 {
+        let mut state = self.clone();
+        let b = ((self.length as u64 & 0xff) << 56) | self.tail;
+        state.compress(b);
+        state.v2 ^= 0xee;
+        for _ in 0..3 {
+            state.round();
+        }
+        let lo = state.v0 ^ state.v1 ^ state.v2 ^ state.v3;
+        state.v1 ^= 0xdd;
+        for _ in 0..3 {
+            state.round();
+        }
+        let hi = state.v0 ^ state.v1 ^ state.v2 ^ state.v3;
+        (hi as u128) << 64 | lo as u128
+    }
+
+    #[inline]
+    fn round(&mut self) {
+        self.v0 = self.v0.wrapping_add(self.v1);
+        self.v1 = self.v1.rotate_left(13) ^ self.v0;
+        self.v0 = self.v0.rotate_left(32);
+        self.v2 = self.v2.wrapping_add(self.v3);
+        self.v3 = self.v3.rotate_left(16) ^ self.v2;
+        self.v0 = self.v0.wrapping_add(self.v3);
+        self.v3 = self.v3.rotate_left(21) ^ self.v0;
+        self.v2 = self.v2.wrapping_add(self.v1);
+        self.v1 = self.v1.rotate_left(17) ^ self.v2;
+        self.v2 = self.v2.rotate_left(32);
+    }
+
+    #[inline]
+    fn compress(&mut self, m: u64) {
+        self.v3 ^= m;
+        self.round();
+        self.v0 ^= m;
+    }
+}
+
+impl Hasher for SipHasher13 {
+    fn write(&mut self, bytes: &[u8]) {
+        self.length += bytes.len();
+        let mut rest = bytes;
+
+        if self.ntail > 0 {
+            let take = (8 - self.ntail).min(rest.len());
+            for (i, &b) in rest[..take].iter().enumerate() {
+                self.tail |= (b as u64) << (8 * (self.ntail + i));
+            }
+            self.ntail += take;
+            rest = &rest[take..];
+            if self.ntail < 8 {
+                return;
+            }
+            let m = self.tail;
+            self.compress(m);
+            self.tail = 0;
+            self.ntail = 0;
+        }
+
+        let mut chunks = rest.chunks_exact(8);
+        for chunk in &mut chunks {
+            self.compress(u64::from_le_bytes(chunk.try_into().unwrap()));
+        }
+        for (i, &b) in chunks.remainder().iter().enumerate() {
+            self.tail |= (b as u64) << (8 * i);
+        }
+        self.ntail = chunks.remainder().len();
+    }
+
+    portable_int_writes!();
+
+    fn finish(&self) -> u64 {
+        let mut state = self.clone();
+        let b = ((self.length as u64 & 0xff) << 56) | self.tail;
+        state.compress(b);
+        state.v2 ^= 0xff;
+        for _ in 0..3 {
+            state.round();
+        }
+        state.v0 ^ state.v1 ^ state.v2 ^ state.v3
+    }
+}
+
+/// Seeded SipHash-1-3 builder: same output on every platform, process and release.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SeededSip13 {
+    seed: u64,
+    k0: u64,
+    k1: u64,
+}
+
+impl SeededSip13 {
+    /// Builder keyed from `seed` (the 128-bit key is expanded with SplitMix64).
+    pub fn with_seed(seed: u64) -> Self {
+        let mut state = seed;
+        let k0 = splitmix64(&mut state);
+        let k1 = splitmix64(&mut state);
+        Self { seed, k0, k1 }
+    }
+}
+
+impl BuildHasher for SeededSip13 {
+    type Hasher = SipHasher13;
+
+    fn build_hasher(&self) -> SipHasher13 {
+        SipHasher13::new_with_keys(self.k0, self.k1)
+    }
+}
+
+impl Hash128Builder for SeededSip13 {
+    fn hash_one_128<T: Hash + ?Sized>(&self, item: &T) -> u128 {
+        let mut h = SipHasher13::new_with_keys_128(self.k0, self.k1);
+        item.hash(&mut h);
+        h.finish128()
+    }
+}
+
+impl PersistentHasher for SeededSip13 {
+    const ID: u8 = 1;
+
+    fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    fn from_seed(seed: u64) -> Self {
+        Self::with_seed(seed)
+    }
+    fn digest128<T: Hash + ?Sized>(&self, item: &T) -> Option<u128> {
+        Some(self.hash_one_128(item))
+    }
+}
+
+/// Hasher produced by `SeededXxh3` (requires "fast-xxh3" feature).
+#[cfg(feature = "fast-xxh3")]
+#[derive(Clone)]
+pub struct Xxh3Hasher(xxhash_rust::xxh3::Xxh3);
+
+#[cfg(feature = "fast-xxh3")]
+impl Hasher for Xxh3Hasher {
+    fn write(&mut self, bytes: &[u8]) {
+        self.0.update(bytes);
+    }
+
+    portable_int_writes!();
+
+    fn finish(&self) -> u64 {
+        self.0.digest()
+    }
+}
+
+#[cfg(feature = "fast-xxh3")]
+impl Xxh3Hasher {
+    /// xxh3-128 digest of everything written so far.
+    pub fn finish128(&self) -> u128 {
+        self.0.digest128()
+    }
+}
+
+/// Seeded xxh3 builder: same output on every platform and process (requires "fast-xxh3" feature).
+#[cfg(feature = "fast-xxh3")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SeededXxh3 {
+    seed: u64,
+}
+
+#[cfg(feature = "fast-xxh3")]
+impl SeededXxh3 {
+    /// Builder using `seed` as the xxh3 seed.
+    pub fn with_seed(seed: u64) -> Self {
+        Self { seed }
+    }
+}
+
+#[cfg(feature = "fast-xxh3")]
+impl BuildHasher for SeededXxh3 {
+    type Hasher = Xxh3Hasher;
+
+    fn build_hasher(&self) -> Xxh3Hasher {
+        Xxh3Hasher(xxhash_rust::xxh3::Xxh3::with_seed(self.seed))
+    }
+}
+
+#[cfg(feature = "fast-xxh3")]
+impl Hash128Builder for SeededXxh3 {
+    fn hash_one_128<T: Hash + ?Sized>(&self, item: &T) -> u128 {
+        let mut h = self.build_hasher();
+        item.hash(&mut h);
+        h.finish128()
+    }
+}
+
+#[cfg(feature = "fast-xxh3")]
+impl PersistentHasher for SeededXxh3 {
+    const ID: u8 = 2;
+
+    fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    fn from_seed(seed: u64) -> Self {
+        Self::with_seed(seed)
+    }
+    fn digest128<T: Hash + ?Sized>(&self, item: &T) -> Option<u128> {
+        Some(self.hash_one_128(item))
+    }
+}
+
+/// Seeded aHash builder (requires "fast-ahash" feature).
+///
+/// Deterministic across processes for the same build, but aHash itself makes
+/// no promise of identical output across CPU features, platforms or aHash
+/// versions; prefer `SeededSip13` or `SeededXxh3` for files that move between
+/// machines.
+#[cfg(feature = "fast-ahash")]
+#[derive(Clone)]
+pub struct SeededAHash {
+    seed: u64,
+    state: ahash::RandomState,
+}
+
+#[cfg(feature = "fast-ahash")]
+impl SeededAHash {
+    /// Builder whose four aHash keys are expanded from `seed` with SplitMix64.
+    pub fn with_seed(seed: u64) -> Self {
+        let mut s = seed;
+        let keys = [splitmix64(&mut s), splitmix64(&mut s), splitmix64(&mut s), splitmix64(&mut s)];
+        Self {
+            seed,
+            state: ahash::RandomState::with_seeds(keys[0], keys[1], keys[2], keys[3]),
+        }
+    }
+}
+
+#[cfg(feature = "fast-ahash")]
+impl core::fmt::Debug for SeededAHash {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SeededAHash").field("seed", &self.seed).finish()
+    }
+}
+
+#[cfg(feature = "fast-ahash")]
+impl BuildHasher for SeededAHash {
+    type Hasher = ahash::AHasher;
+
+    fn build_hasher(&self) -> ahash::AHasher {
+        self.state.build_hasher()
+    }
+}
+
+#[cfg(feature = "fast-ahash")]
+impl PersistentHasher for SeededAHash {
+    const ID: u8 = 3;
+
+    fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    fn from_seed(seed: u64) -> Self {
+        Self::with_seed(seed)
+    }
+}
@@ -0,0 +1,232 @@
+use core::hash::{BuildHasher, Hash};
+
+use std::fmt;
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use crate::hashing::{self, Hash128Fn, KeyHash};
+use crate::{bloom::BloomFilter, math};
+
+/// Point-in-time statistics for one shard.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShardStats {
+    /// Number of inserts routed to the shard.
+    pub items: usize,
+    /// Number of bits set.
+    pub ones: usize,
+    /// Fraction of bits set.
+    pub fill_ratio: f64,
+    /// False-positive rate implied by the fill: `fill_ratio^k`.
+    pub current_fpr: f64,
+}
+
+/// Bloom filter split into independently locked shards.
+///
+/// Each key goes to the shard picked by a mix of its `(h1, h2)` pair, so
+/// writers on different shards never contend and
+/// each shard is a self-contained `BloomFilter` that can be serialized and
+/// shipped on its own.
+pub struct ShardedBloomFilter<S = std::collections::hash_map::RandomState> {
+    shards: Vec<RwLock<BloomFilter<S>>>,
+    // pair derivation shared by every shard (see BloomFilter::hash128)
+    hash128: Option<Hash128Fn<S>>,
+    fingerprint: u32,
+    hasher_builder: S,
+}
+
+impl<S> fmt::Debug for ShardedBloomFilter<S>
+where
+    S: BuildHasher + Clone,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ShardedBloomFilter")
+            .field("shards", &self.shards.len())
+            .field("items", &self.approximate_items())
+            .finish()
+    }
+}
+
+impl ShardedBloomFilter<std::collections::hash_map::RandomState> {
+    /// convenience constructor using default hasher builder and `m` bits per shard.
+    pub fn new(shards: usize, m: usize, k: u32) -> Self {
+        Self::with_hasher(shards, m, k, std::collections::hash_map::RandomState::new())
+    }
+
+    /// convenience constructor splitting capacity `n` evenly across shards at false-positive rate `p`.
+    pub fn new_for_capacity(shards: usize, n: usize, p: f64) -> Self {
+        assert!(shards > 0, "need at least one shard");
+        let per_shard = n.div_ceil(shards).max(1);
+        let m = math::optimal_m(per_shard, p);
+        let k = math::optimal_k(m, per_shard);
+        Self::with_hasher(shards, m, k, std::collections::hash_map::RandomState::new())
+    }
+}
+
+impl<S> ShardedBloomFilter<S>
+where
+    S: BuildHasher + Clone,
+{
+    /// create with explicit hasher builder and `m` bits per shard.
+    pub fn with_hasher(shards: usize, m: usize, k: u32, hasher_builder: S) -> Self {
+        assert!(shards > 0, "need at least one shard");
+        let shards = (0..shards)
+            .map(|_| BloomFilter::with_hasher(m, k, hasher_builder.clone()))
+            .collect();
+        Self::from_shards(shards, hasher_builder)
+    }
+
+    /// Reassemble from shards, e.g. ones restored with `BloomFilter::from_bytes_hasher`.
+    ///
+    /// Shards must be given in shard order, share `m`, `k`, the index
+    /// strategy and the way they derive probe pairs, and have been built
+    /// with the same hasher as `hasher_builder`.
+    pub fn from_shards(shards: Vec<BloomFilter<S>>, hasher_builder: S) -> Self {
+        assert!(!shards.is_empty(), "need at least one shard");
+        let (m, k) = (shards[0].num_bits(), shards[0].num_hashes());
+        assert!(
+            shards.iter().all(|s| s.num_bits() == m && s.num_hashes() == k),
+            "m/k mismatch between shards"
+        );
+        let fingerprint = hashing::hasher_fingerprint(&hasher_builder);
+        assert!(
+            shards.iter().all(|s| s.hasher_fingerprint() == fingerprint),
+            "hasher mismatch between shards"
+        );
+        let derivation = shards[0].pair_derivation();
+        assert!(
+            shards.iter().all(|s| s.pair_derivation() == derivation),
+            "index strategy mismatch between shards"
+        );
+        let hash128 = shards[0].hash128();
+        Self {
+            shards: shards.into_iter().map(RwLock::new).collect(),
+            hash128,
+            fingerprint,
+            hasher_builder,
+        }
+    }
+
+    fn route(&self, (h1, h2): (u64, u64)) -> usize {
+        // fastrange on h1 alone would reuse the bits FastRange and enhanced
+        // double hashing take their first probe from, leaving each shard
+        // only a slice of its bits for that probe
+        ((hashing::murmur64(h1 ^ h2) as u128 * self.shards.len() as u128) >> 64) as usize
+    }
+
+    fn read(&self, shard: usize) -> RwLockReadGuard<'_, BloomFilter<S>> {
+        self.shards[shard].read().unwrap()
+    }
+
+    fn write(&self, shard: usize) -> RwLockWriteGuard<'_, BloomFilter<S>> {
+        self.shards[shard].write().unwrap()
+    }
+
+    /// Index of the shard `item` is routed to.
+    pub fn shard_for<T: Hash>(&self, item: &T) -> usize {
+        self.route(hashing::hash_pair(&self.hasher_builder, item, self.hash128))
+    }
+
+    /// Insert an item, locking only its shard.
+    pub fn insert<T: Hash>(&self, item: &T) {
+        let pair = hashing::hash_pair(&self.hasher_builder, item, self.hash128);
+        self.write(self.route(pair)).insert_prehashed(item, pair);
+    }
+
+    /// Test whether an item is *probably* in the set.
+    pub fn contains<T: Hash>(&self, item: &T) -> bool {
+        let pair = hashing::hash_pair(&self.hasher_builder, item, self.hash128);
+        self.read(self.route(pair)).contains_prehashed(item, pair)
+    }
+
+    /// Hash `item` once for the `_hashed` methods here or on any compatible filter (see `KeyHash`).
+    pub fn hash_key<T: Hash>(&self, item: &T) -> KeyHash {
+        KeyHash::new(hashing::hash_pair(&self.hasher_builder, item, self.hash128), self.fingerprint, self.hash128.is_some())
+    }
+
+    /// `insert` for a key hashed with `hash_key`, locking only its shard.
+    ///
+    /// Panics as `BloomFilter::insert_hashed` does.
+    pub fn insert_hashed(&self, hash: KeyHash) {
+        self.write(self.route(hash.pair())).insert_hashed(hash);
+    }
+
+    /// `contains` for a key hashed with `hash_key`.
+    ///
+    /// Panics as `BloomFilter::insert_hashed` does.
+    pub fn contains_hashed(&self, hash: KeyHash) -> bool {
+        self.read(self.route(hash.pair())).contains_hashed(hash)
+    }
+
+    /// Clear every shard.
+    pub fn clear(&self) {
+        for shard in 0..self.shards.len() {
+            self.write(shard).clear();
+        }
+    }
+
+    /// Number of shards.
+    pub fn num_shards(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Sum of `approximate_items` over all shards.
+    pub fn approximate_items(&self) -> usize {
+        (0..self.shards.len()).map(|i| self.read(i).approximate_items()).sum()
+    }
+
+    /// Statistics for one shard.
+    pub fn shard_stats(&self, shard: usize) -> ShardStats {
+        let filter = self.read(shard);
+        ShardStats {
+            items: filter.approximate_items(),
+            ones: filter.count_ones(),
+            fill_ratio: filter.fill_ratio(),
+            current_fpr: filter.current_fpr(),
+        }
+    }
+
+    /// Statistics for every shard, in shard order.
+    pub fn stats(&self) -> Vec<ShardStats> {
+        (0..self.shards.len()).map(|i| self.shard_stats(i)).collect()
+    }
+
+    /// Copy of one shard as a plain `BloomFilter`.
+    pub fn shard(&self, shard: usize) -> BloomFilter<S> {
+        self.read(shard).clone()
+    }
+
+    /// Serialize one shard in the `BloomFilter::to_bytes` layout.
+    ///
+    /// Panics as `BloomFilter::to_bytes` does; `shard(i).try_to_bytes()`
+    /// checks first and `shard(i).to_versioned_bytes()` keeps a non-default
+    /// strategy.
+    pub fn shard_to_bytes(&self, shard: usize) -> Vec<u8> {
+        self.read(shard).to_bytes()
+    }
+
+    /// Replace one shard, e.g. with a copy received from another node.
+    ///
+    /// The replacement must have the same `m`, `k`, hasher and index
+    /// strategy as the existing shard.
+    pub fn replace_shard(&self, shard: usize, filter: BloomFilter<S>) {
+        let mut current = self.write(shard);
+        assert_eq!(current.num_bits(), filter.num_bits(), "m mismatch for shard");
+        assert_eq!(current.num_hashes(), filter.num_hashes(), "k mismatch for shard");
+        assert_eq!(self.fingerprint, filter.hasher_fingerprint(), "hasher mismatch for shard");
+        assert_eq!(current.pair_derivation(), filter.pair_derivation(), "index strategy mismatch for shard");
+        *current = filter;
+    }
+
+    /// Union of all shards as one `BloomFilter` with the shards' `m` and `k`.
+    ///
+    /// The result has no false negatives, but holds every shard's items in
+    /// one shard's worth of bits, so its false-positive rate is higher.
+    pub fn merge(&self) -> BloomFilter<S> {
+        let mut merged = self.shard(0);
+        for i in 1..self.shards.len() {
+            let shard = self.read(i);
+            merged.union_inplace(&shard);
+            merged.set_approximate_items(merged.approximate_items() + shard.approximate_items());
+        }
+        merged
+    }
+}
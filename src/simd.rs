@@ -0,0 +1,226 @@
+// On x86_64 the widest available instruction set is picked at runtime:
+// AVX2 when the CPU has it, otherwise SSE2 (part of the x86_64 baseline)
+// for the bitwise ops and the `popcnt` instruction, if present, for
+// counting. Other targets use the portable loops, which LLVM vectorizes
+// as far as the compile-time target allows. Every path gives the same
+// result; only the speed differs.
+
+/// Word-wise binary operation applied by `combine`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Op {
+    /// `a | b`
+    Or,
+    /// `a & b`
+    And,
+    /// `a ^ b`
+    Xor,
+    /// `a & !b`
+    AndNot,
+}
+
+impl Op {
+    #[inline]
+    fn word(self, a: u64, b: u64) -> u64 {
+        match self {
+            Op::Or => a | b,
+            Op::And => a & b,
+            Op::Xor => a ^ b,
+            Op::AndNot => a & !b,
+        }
+    }
+}
+
+/// `dst[i] = op(dst[i], src[i])` for every word. Panics if the lengths differ.
+pub(crate) fn combine(op: Op, dst: &mut [u64], src: &[u64]) {
+    assert_eq!(dst.len(), src.len(), "bitset size mismatch");
+    #[cfg(target_arch = "x86_64")]
+    {
+        if std::is_x86_feature_detected!("avx2") {
+            // SAFETY: AVX2 support was checked just above.
+            unsafe { x86::combine_avx2(op, dst, src) }
+        } else {
+            // SAFETY: SSE2 is part of the x86_64 baseline.
+            unsafe { x86::combine_sse2(op, dst, src) }
+        }
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        combine_portable(op, dst, src)
+    }
+}
+
+/// Total number of set bits in `words`.
+pub(crate) fn count_ones(words: &[u64]) -> usize {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if std::is_x86_feature_detected!("avx2") {
+            // SAFETY: AVX2 support was checked just above.
+            return unsafe { x86::count_ones_avx2(words) };
+        }
+        if std::is_x86_feature_detected!("popcnt") {
+            // SAFETY: POPCNT support was checked just above.
+            return unsafe { x86::count_ones_popcnt(words) };
+        }
+    }
+    count_ones_portable(words)
+}
+
+/// Whether any word of `words` is non-zero. Stops at the first set bit.
+pub(crate) fn any(words: &[u64]) -> bool {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if std::is_x86_feature_detected!("avx2") {
+            // SAFETY: AVX2 support was checked just above.
+            unsafe { x86::any_avx2(words) }
+        } else {
+            // SAFETY: SSE2 is part of the x86_64 baseline.
+            unsafe { x86::any_sse2(words) }
+        }
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        any_portable(words)
+    }
+}
+
+fn combine_portable(op: Op, dst: &mut [u64], src: &[u64]) {
+    for (a, b) in dst.iter_mut().zip(src) {
+        *a = op.word(*a, *b);
+    }
+}
+
+fn count_ones_portable(words: &[u64]) -> usize {
+    words.iter().map(|w| w.count_ones() as usize).sum()
+}
+
+fn any_portable(words: &[u64]) -> bool {
+    words.iter().any(|&w| w != 0)
+}
+
+#[cfg(target_arch = "x86_64")]
+mod x86 {
+    use core::arch::x86_64::*;
+
+    use super::Op;
+
+    /// `u64` words per 256-bit and 128-bit register.
+    const AVX2_WORDS: usize = 4;
+    const SSE2_WORDS: usize = 2;
+
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn op_avx2(op: Op, a: __m256i, b: __m256i) -> __m256i {
+        match op {
+            Op::Or => _mm256_or_si256(a, b),
+            Op::And => _mm256_and_si256(a, b),
+            Op::Xor => _mm256_xor_si256(a, b),
+            // andnot computes `!first & second`
+            Op::AndNot => _mm256_andnot_si256(b, a),
+        }
+    }
+
+    #[inline]
+    #[target_feature(enable = "sse2")]
+    unsafe fn op_sse2(op: Op, a: __m128i, b: __m128i) -> __m128i {
+        match op {
+            Op::Or => _mm_or_si128(a, b),
+            Op::And => _mm_and_si128(a, b),
+            Op::Xor => _mm_xor_si128(a, b),
+            Op::AndNot => _mm_andnot_si128(b, a),
+        }
+    }
+
+    /// Caller must ensure the CPU supports AVX2 and `dst.len() == src.len()`.
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn combine_avx2(op: Op, dst: &mut [u64], src: &[u64]) {
+        let mut d = dst.chunks_exact_mut(AVX2_WORDS);
+        let mut s = src.chunks_exact(AVX2_WORDS);
+        for (a, b) in (&mut d).zip(&mut s) {
+            let a_ptr = a.as_mut_ptr() as *mut __m256i;
+            let v = op_avx2(op, _mm256_loadu_si256(a_ptr), _mm256_loadu_si256(b.as_ptr() as *const __m256i));
+            _mm256_storeu_si256(a_ptr, v);
+        }
+        super::combine_portable(op, d.into_remainder(), s.remainder());
+    }
+
+    /// Caller must ensure `dst.len() == src.len()` (SSE2 is always present on x86_64).
+    #[target_feature(enable = "sse2")]
+    pub(super) unsafe fn combine_sse2(op: Op, dst: &mut [u64], src: &[u64]) {
+        let mut d = dst.chunks_exact_mut(SSE2_WORDS);
+        let mut s = src.chunks_exact(SSE2_WORDS);
+        for (a, b) in (&mut d).zip(&mut s) {
+            let a_ptr = a.as_mut_ptr() as *mut __m128i;
+            let v = op_sse2(op, _mm_loadu_si128(a_ptr), _mm_loadu_si128(b.as_ptr() as *const __m128i));
+            _mm_storeu_si128(a_ptr, v);
+        }
+        super::combine_portable(op, d.into_remainder(), s.remainder());
+    }
+
+    /// Nibble-lookup popcount (Mula et al.), summed per 64-bit lane with `vpsadbw`.
+    ///
+    /// Caller must ensure the CPU supports AVX2.
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn count_ones_avx2(words: &[u64]) -> usize {
+        let lookup = _mm256_setr_epi8(
+            0, 1, 1, 2, 1, 2, 2, 3, 1, 2, 2, 3, 2, 3, 3, 4,
+            0, 1, 1, 2, 1, 2, 2, 3, 1, 2, 2, 3, 2, 3, 3, 4,
+        );
+        let low = _mm256_set1_epi8(0x0f);
+        let zero = _mm256_setzero_si256();
+        let mut acc = zero;
+        let chunks = words.chunks_exact(AVX2_WORDS);
+        let tail = chunks.remainder();
+        for c in chunks {
+            let v = _mm256_loadu_si256(c.as_ptr() as *const __m256i);
+            let lo = _mm256_shuffle_epi8(lookup, _mm256_and_si256(v, low));
+            let hi = _mm256_shuffle_epi8(lookup, _mm256_and_si256(_mm256_srli_epi16::<4>(v), low));
+            acc = _mm256_add_epi64(acc, _mm256_sad_epu8(_mm256_add_epi8(lo, hi), zero));
+        }
+        let mut lanes = [0u64; AVX2_WORDS];
+        _mm256_storeu_si256(lanes.as_mut_ptr() as *mut __m256i, acc);
+        lanes.iter().sum::<u64>() as usize + super::count_ones_portable(tail)
+    }
+
+    /// Caller must ensure the CPU supports POPCNT.
+    #[target_feature(enable = "popcnt")]
+    pub(super) unsafe fn count_ones_popcnt(words: &[u64]) -> usize {
+        // spelled out here rather than calling `count_ones_portable`, so the
+        // loop is compiled with `popcnt` enabled
+        words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// Caller must ensure the CPU supports AVX2.
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn any_avx2(words: &[u64]) -> bool {
+        // test one cache line (two registers) per branch
+        let chunks = words.chunks_exact(2 * AVX2_WORDS);
+        let tail = chunks.remainder();
+        for c in chunks {
+            let p = c.as_ptr() as *const __m256i;
+            let v = _mm256_or_si256(_mm256_loadu_si256(p), _mm256_loadu_si256(p.add(1)));
+            if _mm256_testz_si256(v, v) == 0 {
+                return true;
+            }
+        }
+        super::any_portable(tail)
+    }
+
+    /// SSE2 is always present on x86_64.
+    #[target_feature(enable = "sse2")]
+    pub(super) unsafe fn any_sse2(words: &[u64]) -> bool {
+        let zero = _mm_setzero_si128();
+        let chunks = words.chunks_exact(4 * SSE2_WORDS);
+        let tail = chunks.remainder();
+        for c in chunks {
+            let p = c.as_ptr() as *const __m128i;
+            let v = _mm_or_si128(
+                _mm_or_si128(_mm_loadu_si128(p), _mm_loadu_si128(p.add(1))),
+                _mm_or_si128(_mm_loadu_si128(p.add(2)), _mm_loadu_si128(p.add(3))),
+            );
+            if _mm_movemask_epi8(_mm_cmpeq_epi8(v, zero)) != 0xffff {
+                return true;
+            }
+        }
+        super::any_portable(tail)
+    }
+}
@@ -0,0 +1,199 @@
+use core::hash::{BuildHasher, Hash};
+
+use std::fmt;
+
+use crate::{hashing, math};
+
+/// Default cell width in bits (cells count down from 7).
+pub const DEFAULT_CELL_BITS: u32 = 3;
+
+/// Stable bloom filter (Deng & Rafiei, 2006) for deduplicating unbounded streams.
+///
+/// Each of the `m` positions is a `d`-bit cell. An insert first decrements
+/// `P` randomly chosen cells, then sets the item's `k` cells to `2^d - 1`.
+/// Old items fade out at the rate new ones arrive, so the fraction of zero
+/// cells, and with it the false-positive rate, converges to a fixed point
+/// (`math::stable_fpr`) instead of climbing to 1. The price is false
+/// negatives for items not seen recently.
+#[derive(Clone)]
+pub struct StableBloomFilter<S = std::collections::hash_map::RandomState> {
+    cells: Vec<u64>,
+    m: usize,
+    k: u32,
+    cell_bits: u32,
+    decrements: usize,
+    items: usize,
+    rng: u64,
+    hasher_builder: S,
+}
+
+impl<S> fmt::Debug for StableBloomFilter<S>
+where
+    S: BuildHasher + Clone,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StableBloomFilter")
+            .field("m(cells)", &self.m)
+            .field("cell_bits", &self.cell_bits)
+            .field("k", &self.k)
+            .field("decrements", &self.decrements)
+            .field("items", &self.items)
+            .finish()
+    }
+}
+
+impl StableBloomFilter<std::collections::hash_map::RandomState> {
+    /// convenience constructor from a memory budget and a stable false-positive rate `p`.
+    pub fn new(memory_bytes: usize, p: f64) -> Self {
+        Self::for_fpr_with_hasher(memory_bytes, DEFAULT_CELL_BITS, p, std::collections::hash_map::RandomState::new())
+    }
+}
+
+impl<S> StableBloomFilter<S>
+where
+    S: BuildHasher + Clone,
+{
+    /// create with explicit cell count, cell width (1..=8 bits), hash count,
+    /// decrements per insert and hasher builder.
+    pub fn with_hasher(m: usize, cell_bits: u32, k: u32, decrements: usize, hasher_builder: S) -> Self {
+        assert!(m > 0 && k > 0 && (k as usize) < m);
+        assert!((1..=8).contains(&cell_bits), "cell_bits must be in 1..=8");
+        assert!(decrements > 0, "decrements must be > 0");
+        let per_word = 64 / cell_bits as usize;
+        Self {
+            cells: vec![0u64; m.div_ceil(per_word)],
+            m,
+            k,
+            cell_bits,
+            decrements,
+            items: 0,
+            rng: 0x9e37_79b9_7f4a_7c15,
+            hasher_builder,
+        }
+    }
+
+    /// create from a memory budget in bytes and a stable false-positive rate `p`.
+    ///
+    /// The budget fixes `m`; `k` comes from `math::stable_k` and the number of
+    /// decrements from `math::stable_decrements`.
+    pub fn for_fpr_with_hasher(memory_bytes: usize, cell_bits: u32, p: f64, hasher_builder: S) -> Self {
+        assert!((1..=8).contains(&cell_bits), "cell_bits must be in 1..=8");
+        let per_word = 64 / cell_bits as usize;
+        let m = (memory_bytes / 8) * per_word;
+        let k = math::stable_k(p);
+        assert!(m > k as usize, "memory budget too small");
+        let decrements = math::stable_decrements(m, k, cell_bits, p);
+        Self::with_hasher(m, cell_bits, k, decrements, hasher_builder)
+    }
+
+    fn cell_max(&self) -> u64 {
+        (1u64 << self.cell_bits) - 1
+    }
+
+    fn cell(&self, idx: usize) -> u64 {
+        let per_word = 64 / self.cell_bits as usize;
+        let shift = (idx % per_word) * self.cell_bits as usize;
+        (self.cells[idx / per_word] >> shift) & self.cell_max()
+    }
+
+    fn set_cell(&mut self, idx: usize, value: u64) {
+        let per_word = 64 / self.cell_bits as usize;
+        let shift = (idx % per_word) * self.cell_bits as usize;
+        let mask = self.cell_max() << shift;
+        let word = &mut self.cells[idx / per_word];
+        *word = (*word & !mask) | (value << shift);
+    }
+
+    fn indices<T: Hash>(&self, item: &T) -> impl Iterator<Item = usize> {
+        let (h1, h2) = hashing::hash2(&self.hasher_builder, item);
+        let m = self.m;
+        (0..self.k).map(move |i| hashing::nth_index(h1, h2, i, m))
+    }
+
+    fn decrement_random(&mut self) {
+        for _ in 0..self.decrements {
+            let r = hashing::splitmix64(&mut self.rng);
+            let idx = ((r as u128 * self.m as u128) >> 64) as usize;
+            let c = self.cell(idx);
+            if c > 0 {
+                self.set_cell(idx, c - 1);
+            }
+        }
+    }
+
+    /// Insert an item: decrement `P` random cells, then set the item's `k` cells to the maximum.
+    pub fn insert<T: Hash>(&mut self, item: &T) {
+        self.decrement_random();
+        let max = self.cell_max();
+        let indices: Vec<usize> = self.indices(item).collect();
+        for idx in indices {
+            self.set_cell(idx, max);
+        }
+        self.items = self.items.saturating_add(1);
+    }
+
+    /// Test whether an item is *probably* in the set (i.e. seen recently).
+    pub fn contains<T: Hash>(&self, item: &T) -> bool {
+        self.indices(item).all(|idx| self.cell(idx) > 0)
+    }
+
+    /// Test membership, then insert. Returns `true` if the item was
+    /// probably seen before, which is what a stream deduplicator needs.
+    pub fn check_and_insert<T: Hash>(&mut self, item: &T) -> bool {
+        let seen = self.contains(item);
+        self.insert(item);
+        seen
+    }
+
+    /// Reset all cells and the item counter to zero.
+    pub fn clear(&mut self) {
+        self.cells.fill(0);
+        self.items = 0;
+    }
+
+    /// Number of times `insert` was called.
+    pub fn approximate_items(&self) -> usize {
+        self.items
+    }
+
+    /// Number of cells (`m`).
+    pub fn num_cells(&self) -> usize {
+        self.m
+    }
+
+    /// Width of each cell in bits (`d`).
+    pub fn cell_bits(&self) -> u32 {
+        self.cell_bits
+    }
+
+    /// Number of hash functions (`k`).
+    pub fn num_hashes(&self) -> u32 {
+        self.k
+    }
+
+    /// Cells decremented per insert (`P`).
+    pub fn decrements(&self) -> usize {
+        self.decrements
+    }
+
+    /// Fraction of cells currently at zero.
+    pub fn zero_fraction(&self) -> f64 {
+        let zeros = (0..self.m).filter(|&idx| self.cell(idx) == 0).count();
+        zeros as f64 / self.m as f64
+    }
+
+    /// False-positive rate implied by the current cell contents: `(1 - zeros)^k`.
+    pub fn current_fpr(&self) -> f64 {
+        (1.0 - self.zero_fraction()).powi(self.k as i32)
+    }
+
+    /// False-positive rate the filter converges to (`math::stable_fpr`).
+    pub fn stable_fpr(&self) -> f64 {
+        math::stable_fpr(self.m, self.k, self.cell_bits, self.decrements)
+    }
+
+    /// Size of the cell array in bytes.
+    pub fn size_in_bytes(&self) -> usize {
+        self.cells.len() * 8
+    }
+}
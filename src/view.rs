@@ -0,0 +1,162 @@
+use core::hash::{BuildHasher, Hash};
+use std::fmt;
+
+use crate::format::{self, FormatError};
+use crate::hashing::{self, Hash128Fn};
+use crate::seeded::PersistentHasher;
+use crate::index::IndexStrategy;
+use crate::{bitset::BitSet, BloomFilter};
+
+/// Read-only bloom filter answering `contains` straight from borrowed bytes.
+///
+/// The layout is validated once on construction; after that every query
+/// reads the payload in place, without copying or allocating. Bits are
+/// addressed byte-wise (bit `i` lives in byte `i / 8` of the little-endian
+/// words), so the slice needs no particular alignment and the result is the
+/// same on big-endian hosts.
+#[derive(Clone)]
+pub struct BloomFilterRef<'a, S = std::collections::hash_map::RandomState> {
+    payload: &'a [u8],
+    m: usize,
+    k: u32,
+    items: usize,
+    strategy: IndexStrategy,
+    hash128: Option<Hash128Fn<S>>,
+    hasher_builder: S,
+}
+
+impl<S> fmt::Debug for BloomFilterRef<'_, S>
+where
+    S: BuildHasher + Clone,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BloomFilterRef")
+            .field("m(bits)", &self.m)
+            .field("k", &self.k)
+            .field("items", &self.items)
+            .finish()
+    }
+}
+
+impl<'a, S> BloomFilterRef<'a, S>
+where
+    S: BuildHasher + Clone,
+{
+    /// View legacy `BloomFilter::to_bytes` data with an explicit hasher builder.
+    ///
+    /// Returns `None` if the data length or internal layout is invalid.
+    pub fn from_bytes_hasher(data: &'a [u8], hasher_builder: S) -> Option<Self> {
+        let (m, k, payload) = format::open_legacy(data)?;
+        let m = usize::try_from(m).ok()?;
+        if m == 0 || k == 0 {
+            return None;
+        }
+        Some(Self { payload, m, k, items: 0, strategy: IndexStrategy::DoubleHashing, hash128: hashing::auto_hash128::<S>(), hasher_builder })
+    }
+
+    /// Check whether an item is probably in the set.
+    pub fn contains<T: Hash>(&self, item: &T) -> bool {
+        contains_in(self.payload, self.m, self.k, self.strategy, self.hash128, &self.hasher_builder, item)
+    }
+
+    /// Number of bits (`m`).
+    pub fn num_bits(&self) -> usize {
+        self.m
+    }
+
+    /// Number of hash functions (`k`).
+    pub fn num_hashes(&self) -> u32 {
+        self.k
+    }
+
+    /// Item counter stored with the data (`0` for legacy data).
+    pub fn approximate_items(&self) -> usize {
+        self.items
+    }
+
+    /// Strategy used to derive bit positions.
+    pub fn index_strategy(&self) -> IndexStrategy {
+        self.strategy
+    }
+
+    /// Hasher builder used to derive bit positions.
+    pub fn hasher_builder(&self) -> &S {
+        &self.hasher_builder
+    }
+
+    /// Copy the viewed bits into an owned `BloomFilter`.
+    pub fn to_bloom_filter(&self) -> BloomFilter<S> {
+        let words = self
+            .payload
+            .chunks_exact(8)
+            .map(|c| u64::from_le_bytes(c.try_into().unwrap()))
+            .collect();
+        let mut bf = BloomFilter::from_parts(BitSet::from_words(self.m, words), self.k, self.items, self.hasher_builder.clone());
+        bf.set_index_strategy(self.strategy);
+        bf.set_hash128(self.hash128);
+        bf
+    }
+}
+
+/// Membership test against little-endian bit words stored as bytes.
+#[inline]
+pub(crate) fn contains_in<T: Hash, S: BuildHasher>(
+    payload: &[u8],
+    m: usize,
+    k: u32,
+    strategy: IndexStrategy,
+    hash128: Option<Hash128Fn<S>>,
+    state: &S,
+    item: &T,
+) -> bool {
+    strategy.probe(state, item, hash128, k, m, |idx| payload[idx >> 3] & (1 << (idx & 7)) != 0)
+}
+
+impl<'a, S> BloomFilterRef<'a, S>
+where
+    S: PersistentHasher,
+{
+    /// View versioned (`to_versioned_bytes`) or legacy data, rebuilding the hasher from the stored seed.
+    ///
+    /// The checksum is verified once here. Legacy data is only accepted for
+    /// `ID == 0` builders, as in `BloomFilter::from_versioned_bytes`.
+    pub fn from_versioned_bytes(data: &'a [u8]) -> Result<Self, FormatError> {
+        if !data.starts_with(&format::MAGIC) {
+            if S::ID != 0 {
+                return Err(FormatError::MissingHasher);
+            }
+            return Self::from_bytes_hasher(data, S::from_seed(0)).ok_or(FormatError::InvalidLayout);
+        }
+        let (header, payload) = format::open(data)?;
+        let (hasher_builder, hash128) = format::resolve_hasher(&header, None)?;
+        Self::from_container(&header, payload, hasher_builder, hash128)
+    }
+
+    /// View versioned or legacy data with an explicit hasher builder.
+    pub fn from_versioned_bytes_hasher(data: &'a [u8], hasher_builder: S) -> Result<Self, FormatError> {
+        if !data.starts_with(&format::MAGIC) {
+            return Self::from_bytes_hasher(data, hasher_builder).ok_or(FormatError::InvalidLayout);
+        }
+        let (header, payload) = format::open(data)?;
+        let (hasher_builder, hash128) = format::resolve_hasher(&header, Some(hasher_builder))?;
+        Self::from_container(&header, payload, hasher_builder, hash128)
+    }
+
+    fn from_container(
+        header: &format::Header,
+        payload: &'a [u8],
+        hasher_builder: S,
+        hash128: Option<Hash128Fn<S>>,
+    ) -> Result<Self, FormatError> {
+        let m = format::bloom_bits(header, payload)?;
+        Ok(Self {
+            payload,
+            m,
+            k: header.k,
+            items: header.items as usize,
+            strategy: header.strategy,
+            hash128,
+            hasher_builder,
+        })
+    }
+}
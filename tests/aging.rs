@@ -0,0 +1,76 @@
+use bloomz::{AgingBloomFilter, ManualClock, Rotation};
+use std::collections::hash_map::RandomState;
+use std::time::Duration;
+
+fn minutes(n: u64) -> Duration {
+    Duration::from_secs(60 * n)
+}
+
+#[test]
+fn items_expire_after_window() {
+    let clock = ManualClock::new();
+    // 4 generations of one minute: remembered for at least 3 minutes
+    let mut filter = AgingBloomFilter::with_clock(4, 1_000, 0.01, Rotation::Interval(minutes(1)), RandomState::new(), clock.clone());
+    filter.insert(&"req-1");
+
+    for _ in 0..3 {
+        clock.advance(minutes(1));
+        assert!(filter.contains(&"req-1"));
+    }
+    clock.advance(minutes(1));
+    assert!(!filter.contains(&"req-1"), "expired before expire() ran");
+    filter.expire();
+    assert!(!filter.contains(&"req-1"));
+    assert_eq!(filter.approximate_items(), 0);
+}
+
+#[test]
+fn sliding_window_of_inserts() {
+    let clock = ManualClock::new();
+    let mut filter = AgingBloomFilter::with_clock(3, 10_000, 0.01, Rotation::Interval(Duration::from_secs(10)), RandomState::new(), clock.clone());
+    for second in 0..120u64 {
+        filter.insert(&second);
+        clock.advance(Duration::from_secs(1));
+    }
+    // now = 120s; the last two full periods (100..120) are guaranteed live
+    assert!((100..120u64).all(|s| filter.contains(&s)));
+    let stale = (0..90u64).filter(|s| filter.contains(s)).count();
+    assert!(stale < 10, "{} expired items still reported", stale);
+    println!("live items: {}", filter.approximate_items());
+    assert_eq!(filter.approximate_items(), 20);
+}
+
+#[test]
+fn long_idle_expires_everything_and_reuses_generations() {
+    let clock = ManualClock::new();
+    let mut filter = AgingBloomFilter::with_clock(2, 100, 0.01, Rotation::Interval(minutes(5)), RandomState::new(), clock.clone());
+    let sizes: Vec<usize> = filter.generations().map(|g| g.num_bits()).collect();
+    filter.insert(&1u32);
+    clock.advance(minutes(600));
+    assert!(!filter.contains(&1u32));
+    filter.insert(&2u32);
+    assert!(filter.contains(&2u32));
+    assert_eq!(filter.generations().map(|g| g.num_bits()).collect::<Vec<_>>(), sizes);
+
+    // period boundaries stay aligned after the idle stretch
+    clock.advance(minutes(4));
+    assert!(filter.contains(&2u32));
+}
+
+#[test]
+fn rotation_by_insert_count() {
+    let mut filter = AgingBloomFilter::new(3, 100, 0.01, Rotation::Inserts(100));
+    for i in 0..1_000u32 {
+        filter.insert(&i);
+    }
+    // the newest 200-300 inserts are live
+    assert!((800..1_000u32).all(|i| filter.contains(&i)));
+    let old_fp = (0..700u32).filter(|i| filter.contains(i)).count();
+    println!("old items still reported: {}", old_fp);
+    assert!(old_fp < 60);
+    assert_eq!(filter.approximate_items(), 300);
+
+    filter.clear();
+    assert_eq!(filter.approximate_items(), 0);
+    assert!(!filter.contains(&999u32));
+}
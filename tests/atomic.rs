@@ -0,0 +1,78 @@
+use bloomz::{AtomicBloomFilter, BloomFilter};
+use std::collections::hash_map::RandomState;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+#[test]
+fn concurrent_inserts_through_shared_ref() {
+    let filter = AtomicBloomFilter::new_for_capacity(80_000, 0.01);
+    thread::scope(|s| {
+        for t in 0..8u64 {
+            let filter = &filter;
+            s.spawn(move || {
+                for i in 0..10_000u64 {
+                    filter.insert(&(t * 1_000_000 + i));
+                }
+            });
+        }
+    });
+    assert_eq!(filter.approximate_items(), 80_000);
+    for t in 0..8u64 {
+        for i in 0..10_000u64 {
+            assert!(filter.contains(&(t * 1_000_000 + i)));
+        }
+    }
+}
+
+#[test]
+fn insert_if_absent_reports_new_keys() {
+    let filter = AtomicBloomFilter::new_for_capacity(10_000, 0.001);
+    assert!(filter.insert_if_absent(&"a"));
+    assert!(!filter.insert_if_absent(&"a"));
+    assert!(filter.insert_if_absent(&"b"));
+    assert_eq!(filter.approximate_items(), 2);
+
+    // threads racing over the same keys: every key is reported new at least
+    // once, and duplicates after the race never are
+    let firsts = AtomicUsize::new(0);
+    thread::scope(|s| {
+        for _ in 0..4 {
+            s.spawn(|| {
+                for i in 0..2_000u32 {
+                    if filter.insert_if_absent(&i) {
+                        firsts.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+            });
+        }
+    });
+    let firsts = firsts.into_inner();
+    println!("keys reported new: {} of 2000", firsts);
+    assert!(firsts >= 1_990, "too few new keys: {}", firsts);
+    assert!((0..2_000u32).all(|i| !filter.insert_if_absent(&i)));
+}
+
+#[test]
+fn converts_to_and_from_bloom_filter() {
+    let rs = RandomState::new();
+    let mut plain = BloomFilter::with_hasher(10_000, 5, rs.clone());
+    for i in 0..500u32 {
+        plain.insert(&i);
+    }
+    let atomic = AtomicBloomFilter::from(plain.clone());
+    assert_eq!(atomic.approximate_items(), 500);
+    assert!((0..500u32).all(|i| atomic.contains(&i)));
+    atomic.insert(&10_000u32);
+
+    let snapshot = atomic.to_bloom_filter();
+    assert!(snapshot.contains(&10_000u32) && !plain.contains(&10_000u32));
+    assert_eq!(snapshot.to_bytes(), atomic.to_bytes());
+
+    let bits = atomic.to_bitset();
+    let rebuilt = AtomicBloomFilter::from_bitset(bits, 5, rs.clone());
+    let back: BloomFilter<RandomState> = rebuilt.into();
+    assert_eq!(back.to_bytes(), snapshot.to_bytes());
+
+    let restored = AtomicBloomFilter::from_bytes_hasher(&atomic.to_bytes(), rs).unwrap();
+    assert!((0..500u32).all(|i| restored.contains(&i)));
+}
@@ -0,0 +1,63 @@
+use bloomz::{BloomFilter, IndexStrategy, SeededSip13};
+use std::collections::hash_map::RandomState;
+
+const ALL: [IndexStrategy; 5] = [
+    IndexStrategy::DoubleHashing,
+    IndexStrategy::FastRange,
+    IndexStrategy::EnhancedDoubleHashing,
+    IndexStrategy::TripleHashing,
+    IndexStrategy::Independent,
+];
+
+#[test]
+fn contains_many_matches_contains() {
+    let rs = RandomState::new();
+    for strategy in ALL {
+        let mut bf = BloomFilter::with_hasher_and_strategy(20_000, 6, rs.clone(), strategy);
+        for i in 0..2_000u32 {
+            bf.insert(&i);
+        }
+        // lengths around the window size exercise partial windows
+        for len in [0, 1, 31, 32, 33, 64, 1_000] {
+            let queries: Vec<u32> = (0..len).map(|i| i * 3).collect();
+            let expected: Vec<bool> = queries.iter().map(|q| bf.contains(q)).collect();
+            assert_eq!(bf.contains_many(&queries), expected, "{:?} len {}", strategy, len);
+        }
+        let queries: Vec<u32> = (1_000..5_000).collect();
+        let hits = bf.contains_many(&queries).iter().filter(|&&b| b).count();
+        println!("{:?}: {} of {} probably present", strategy, hits, queries.len());
+        assert!((1_000..1_100).contains(&hits));
+    }
+}
+
+#[test]
+fn mask_has_one_bit_per_item() {
+    let mut bf = BloomFilter::with_hasher(50_000, 5, SeededSip13::with_seed(3));
+    let present: Vec<String> = (0..100).map(|i| format!("key-{}", i)).collect();
+    for key in &present {
+        bf.insert(key);
+    }
+    let queries: Vec<String> = (0..130).map(|i| format!("key-{}", i * 2)).collect();
+    let mask = bf.contains_many_mask(&queries);
+    assert_eq!(mask.len(), 3);
+    for (i, q) in queries.iter().enumerate() {
+        assert_eq!(mask[i / 64] >> (i % 64) & 1 == 1, bf.contains(q), "query {}", i);
+    }
+    // no stray bits past the last item
+    assert_eq!(mask[2] >> 2, 0);
+}
+
+#[test]
+fn insert_many_matches_insert() {
+    for strategy in ALL {
+        let items: Vec<u64> = (0..10_000).chain(0..500).collect();
+        let mut one_by_one = BloomFilter::with_hasher_and_strategy(100_003, 7, SeededSip13::with_seed(9), strategy);
+        let mut batched = one_by_one.clone();
+        for item in &items {
+            one_by_one.insert(item);
+        }
+        batched.insert_many(&items);
+        assert_eq!(batched.to_versioned_bytes(), one_by_one.to_versioned_bytes(), "{:?}", strategy);
+        assert_eq!(batched.approximate_items(), 10_500);
+    }
+}
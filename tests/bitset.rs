@@ -0,0 +1,104 @@
+use bloomz::bitset::BitSet;
+use bloomz::BloomFilter;
+use std::collections::hash_map::RandomState;
+
+/// An in-place `BitSet` op paired with its word-level reference.
+type Case = (fn(&mut BitSet, &BitSet), fn(u64, u64) -> u64);
+
+/// Pseudo-random set of `bits` bits (splitmix64), padding bits left clear.
+fn random_set(bits: usize, seed: u64) -> BitSet {
+    let mut set = BitSet::new(bits);
+    let mut state = seed;
+    for w in set.words_mut() {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        *w = z ^ (z >> 31);
+    }
+    if !bits.is_multiple_of(64) {
+        *set.words_mut().last_mut().unwrap() &= (1u64 << (bits % 64)) - 1;
+    }
+    set
+}
+
+#[test]
+fn bulk_ops_match_word_loops() {
+    // sizes around the 2-, 4- and 8-word vector chunks, plus a large one
+    for bits in (0..=40).map(|w| w * 64 + 13).chain([1 << 20]) {
+        let (a, b) = (random_set(bits, bits as u64), random_set(bits, !(bits as u64)));
+        let cases: [Case; 4] = [
+            (BitSet::or_with, |x, y| x | y),
+            (BitSet::and_with, |x, y| x & y),
+            (BitSet::xor_with, |x, y| x ^ y),
+            (BitSet::and_not_with, |x, y| x & !y),
+        ];
+        for (i, (op, word)) in cases.into_iter().enumerate() {
+            let mut got = a.clone();
+            op(&mut got, &b);
+            let expected: Vec<u64> = a.words_slice().iter().zip(b.words_slice()).map(|(&x, &y)| word(x, y)).collect();
+            assert_eq!(got.words_slice(), &expected[..], "op {} at {} bits", i, bits);
+        }
+    }
+}
+
+#[test]
+fn count_ones_and_any() {
+    for bits in (0..=40).map(|w| w * 64 + 5).chain([1 << 20]) {
+        let set = random_set(bits, 7 * bits as u64);
+        let expected: usize = set.words_slice().iter().map(|w| w.count_ones() as usize).sum();
+        assert_eq!(set.count_ones(), expected, "{} bits", bits);
+        assert_eq!(set.any(), expected > 0);
+    }
+    // a single set bit is found wherever it sits
+    let bits = 70 * 64;
+    for idx in (0..bits).step_by(61).chain([bits - 1]) {
+        let mut set = BitSet::new(bits);
+        assert!(!set.any());
+        set.set(idx);
+        assert!(set.any(), "bit {}", idx);
+        assert_eq!(set.count_ones(), 1);
+    }
+}
+
+#[test]
+#[should_panic(expected = "bitset size mismatch")]
+fn bulk_op_size_mismatch_panics() {
+    let mut a = BitSet::new(128);
+    a.xor_with(&BitSet::new(192));
+}
+
+#[test]
+fn bloom_fill_stats() {
+    let mut bf = BloomFilter::with_hasher(1 << 20, 7, RandomState::new());
+    assert!(bf.is_empty());
+    assert_eq!(bf.current_fpr(), 0.0);
+    for i in 0..50_000u32 {
+        bf.insert(&i);
+    }
+    // duplicates bump the item counter but not the fill
+    for i in 0..50_000u32 {
+        bf.insert(&i);
+    }
+    let estimate = bf.estimated_cardinality();
+    println!(
+        "ones {}, fill {:.4}, fpr {:.6}, cardinality {:.0} (items {})",
+        bf.count_ones(),
+        bf.fill_ratio(),
+        bf.current_fpr(),
+        estimate,
+        bf.approximate_items()
+    );
+    assert!(!bf.is_empty());
+    assert!((estimate - 50_000.0).abs() < 1_000.0);
+    assert!(bf.current_fpr() < 0.01);
+
+    let mut other = BloomFilter::with_hasher(1 << 20, 7, bf.hasher_builder().clone());
+    for i in 50_000..100_000u32 {
+        other.insert(&i);
+    }
+    bf.union_inplace(&other);
+    assert!((bf.estimated_cardinality() - 100_000.0).abs() < 2_000.0);
+    bf.clear();
+    assert!(bf.is_empty() && bf.count_ones() == 0);
+}
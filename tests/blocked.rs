@@ -0,0 +1,73 @@
+use bloomz::{BlockSize, BlockedBloomFilter};
+use std::collections::hash_map::RandomState;
+
+fn observed_fpr(bf: &BlockedBloomFilter<RandomState>, from: u64, trials: u64) -> f64 {
+    let fp = (from..from + trials).filter(|i| bf.contains(i)).count();
+    fp as f64 / trials as f64
+}
+
+#[test]
+fn cache_line_insert_contains() {
+    let mut bf = BlockedBloomFilter::with_hasher(10_000, 6, BlockSize::CacheLine512, RandomState::new());
+    assert_eq!(bf.num_bits() % 512, 0);
+    for i in 0..500u32 {
+        bf.insert(&i);
+    }
+    for i in 0..500u32 {
+        assert!(bf.contains(&i), "missing {}", i);
+    }
+    assert!(!bf.contains(&"this-is-ridiculous-and-unseen"));
+}
+
+#[test]
+fn sized_filters_meet_target_fpr() {
+    let n = 10_000usize;
+    let p = 0.01;
+    for block in [BlockSize::CacheLine512, BlockSize::Register64] {
+        let mut bf = BlockedBloomFilter::for_capacity_with_hasher(n, p, block, RandomState::new());
+        assert!(bf.num_bits() >= bloomz::math::optimal_m(n, p));
+        for i in 0..n as u64 {
+            bf.insert(&i);
+        }
+        for i in 0..n as u64 {
+            assert!(bf.contains(&i));
+        }
+        let rate = observed_fpr(&bf, n as u64, 20_000);
+        println!("{:?}: m={} k={} observed FP {:.4}, predicted {:.4}", block, bf.num_bits(), bf.num_hashes(), rate, bf.estimated_fpr());
+        assert!(rate <= p * 2.0 + 0.002, "false positive rate too high: {}", rate);
+    }
+}
+
+#[test]
+fn blocked_fpr_matches_standard_limit() {
+    // with one huge block the formula degenerates to the classic estimate
+    let (m, n, k) = (100_000usize, 10_000usize, 7u32);
+    let classic = (1.0 - (-(k as f64) * n as f64 / m as f64).exp()).powi(k as i32);
+    let blocked = bloomz::math::blocked_fpr(m, n, k, m);
+    assert!((blocked - classic).abs() < 1e-3, "{} vs {}", blocked, classic);
+    assert!(bloomz::math::blocked_fpr(m, n, k, 64) > bloomz::math::blocked_fpr(m, n, k, 512));
+}
+
+#[test]
+fn set_ops_and_roundtrip() {
+    let rs = RandomState::new();
+    let mut a = BlockedBloomFilter::with_hasher(4_096, 5, BlockSize::Register64, rs.clone());
+    let mut b = BlockedBloomFilter::with_hasher(4_096, 5, BlockSize::Register64, rs.clone());
+    for i in 0..200u32 {
+        a.insert(&i);
+    }
+    for i in 150..350u32 {
+        b.insert(&i);
+    }
+    let mut inter = a.clone();
+    inter.intersect_inplace(&b);
+    assert!(inter.contains(&175u32));
+    a.union_inplace(&b);
+    assert!(a.contains(&10u32) && a.contains(&300u32));
+
+    let restored = BlockedBloomFilter::from_bytes_hasher(&a.to_bytes(), rs).expect("deserialize");
+    assert_eq!(restored.block_size(), BlockSize::Register64);
+    for i in 0..350u32 {
+        assert!(restored.contains(&i));
+    }
+}
@@ -0,0 +1,77 @@
+use bloomz::counting::COUNTER_MAX;
+use bloomz::{CountingBloomFilter, CountingError};
+use std::collections::hash_map::RandomState;
+
+#[test]
+fn insert_remove_contains() {
+    let mut cbf = CountingBloomFilter::with_hasher(10_000, 4, RandomState::new());
+    cbf.insert(&"token-a").unwrap();
+    cbf.insert(&"token-b").unwrap();
+    assert!(cbf.contains(&"token-a"));
+    assert!(cbf.contains(&"token-b"));
+
+    cbf.remove(&"token-a").unwrap();
+    assert!(!cbf.contains(&"token-a"));
+    assert!(cbf.contains(&"token-b"));
+    assert_eq!(cbf.approximate_items(), 1);
+}
+
+#[test]
+fn remove_absent_reports_underflow() {
+    let mut cbf = CountingBloomFilter::with_hasher(1_000, 4, RandomState::new());
+    cbf.insert(&1u32).unwrap();
+    assert_eq!(cbf.remove(&"never-inserted"), Err(CountingError::Underflow));
+    assert!(cbf.contains(&1u32), "failed remove must not modify the filter");
+}
+
+#[test]
+fn saturation_reports_overflow_and_sticks() {
+    let mut cbf = CountingBloomFilter::with_hasher(1_000, 3, RandomState::new());
+    let mut results = Vec::new();
+    for _ in 0..COUNTER_MAX as usize + 2 {
+        results.push(cbf.insert(&"hot"));
+    }
+    assert!(results[0].is_ok());
+    assert_eq!(results.last(), Some(&Err(CountingError::Overflow)));
+    assert_eq!(cbf.count_estimate(&"hot"), COUNTER_MAX);
+    assert!(cbf.saturated_counters() > 0);
+
+    // Saturated counters never decrement, so the key cannot be removed fully.
+    for _ in 0..COUNTER_MAX as usize + 2 {
+        cbf.remove(&"hot").unwrap();
+    }
+    assert!(cbf.contains(&"hot"));
+}
+
+#[test]
+fn count_estimate_tracks_duplicates() {
+    let mut cbf = CountingBloomFilter::with_hasher(10_000, 4, RandomState::new());
+    for _ in 0..3 {
+        cbf.insert(&"dup").unwrap();
+    }
+    assert!(cbf.count_estimate(&"dup") >= 3);
+    assert_eq!(cbf.count_estimate(&"missing-key-xyz"), 0);
+}
+
+#[test]
+fn union_and_conversion_to_bloom() {
+    let rs = RandomState::new();
+    let mut a = CountingBloomFilter::with_hasher(5_000, 4, rs.clone());
+    let mut b = CountingBloomFilter::with_hasher(5_000, 4, rs.clone());
+    for i in 0..200u32 {
+        a.insert(&i).unwrap();
+    }
+    for i in 200..400u32 {
+        b.insert(&i).unwrap();
+    }
+    a.union_inplace(&b).unwrap();
+    let bf = a.to_bloom_filter();
+    for i in 0..400u32 {
+        assert!(a.contains(&i));
+        assert!(bf.contains(&i), "bloom conversion lost {}", i);
+    }
+    assert_eq!(bf.approximate_items(), 400);
+
+    a.clear();
+    assert!(!a.contains(&1u32));
+}
@@ -0,0 +1,78 @@
+use bloomz::{CuckooError, CuckooFilter};
+use std::collections::hash_map::RandomState;
+
+#[test]
+fn insert_contains_remove() {
+    let mut cf = CuckooFilter::with_hasher(1_000, 16, 4, RandomState::new());
+    for i in 0..500u32 {
+        cf.insert(&i).unwrap();
+    }
+    assert_eq!(cf.len(), 500);
+    for i in 0..500u32 {
+        assert!(cf.contains(&i), "missing {}", i);
+    }
+    for i in 0..250u32 {
+        assert!(cf.remove(&i));
+    }
+    assert_eq!(cf.len(), 250);
+    let still_there = (0..250u32).filter(|i| cf.contains(i)).count();
+    assert!(still_there < 5, "removed items still reported: {}", still_there);
+    for i in 250..500u32 {
+        assert!(cf.contains(&i));
+    }
+    assert!(!cf.remove(&"never-inserted"));
+}
+
+#[test]
+fn odd_fingerprint_widths_pack_correctly() {
+    for bits in [5u32, 7, 13, 31] {
+        let mut cf = CuckooFilter::with_hasher(2_000, bits, 3, RandomState::new());
+        for i in 0..1_500u32 {
+            cf.insert(&i).unwrap();
+        }
+        for i in 0..1_500u32 {
+            assert!(cf.contains(&i), "{}-bit filter missing {}", bits, i);
+        }
+    }
+}
+
+#[test]
+fn full_filter_reports_error_and_keeps_items() {
+    let mut cf = CuckooFilter::with_hasher(64, 12, 2, RandomState::new());
+    let mut inserted = Vec::new();
+    let mut err = None;
+    for i in 0..10_000u32 {
+        match cf.insert(&i) {
+            Ok(()) => inserted.push(i),
+            Err(e) => {
+                err = Some(e);
+                break;
+            }
+        }
+    }
+    assert_eq!(err, Some(CuckooError::Full));
+    println!("filled {} / {} slots", cf.len(), cf.capacity());
+    assert!(cf.load_factor() > 0.5);
+    for i in &inserted {
+        assert!(cf.contains(i), "lost {} after failed insert", i);
+    }
+}
+
+#[test]
+fn false_positive_rate_matches_fingerprint_size() {
+    let n = 10_000u64;
+    let p = 0.001;
+    let mut cf = CuckooFilter::new_for_capacity(n as usize, p);
+    for i in 0..n {
+        cf.insert(&i).unwrap();
+    }
+    let trials = 50_000u64;
+    let fp = (n..n + trials).filter(|i| cf.contains(i)).count();
+    let rate = fp as f64 / trials as f64;
+    println!("{}-bit fingerprints: observed FP {:.4}, bound {:.4}", cf.fingerprint_bits(), rate, cf.estimated_fpr());
+    assert!(rate <= p * 1.5, "false positive rate too high: {}", rate);
+
+    let bloom_bytes = bloomz::math::optimal_m(n as usize, p) / 8;
+    println!("cuckoo {} bytes vs bloom {} bytes", cf.size_in_bytes(), bloom_bytes);
+    assert!(cf.size_in_bytes() < bloom_bytes);
+}
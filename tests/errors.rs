@@ -0,0 +1,94 @@
+use bloomz::{BloomError, BloomFilter, SeededSip13};
+use std::collections::hash_map::RandomState;
+
+#[test]
+fn bad_parameters_are_reported() {
+    assert_eq!(BloomFilter::try_new(0, 3).unwrap_err(), BloomError::InvalidParams("m must be > 0"));
+    assert!(matches!(BloomFilter::try_new(64, 0), Err(BloomError::InvalidParams(_))));
+    for p in [0.0, 1.0, -0.5, f64::NAN] {
+        assert!(matches!(BloomFilter::try_new_for_capacity(100, p), Err(BloomError::InvalidParams(_))), "p = {}", p);
+    }
+    assert!(BloomFilter::try_new_for_capacity(0, 0.01).is_err());
+
+    let bf = BloomFilter::try_new_for_capacity(1_000, 0.01).unwrap();
+    assert_eq!(bf.design_params(), Some((1_000, 0.01)));
+    let err = BloomFilter::try_with_hasher(0, 1, RandomState::new()).unwrap_err();
+    println!("{}", err);
+}
+
+#[test]
+fn set_ops_report_mismatch_and_leave_filter_untouched() {
+    let rs = RandomState::new();
+    let mut a = BloomFilter::with_hasher(1_024, 4, rs.clone());
+    let mut b = BloomFilter::with_hasher(1_024, 4, rs.clone());
+    let c = BloomFilter::with_hasher(2_048, 4, rs);
+    a.insert(&1u32);
+    b.insert(&2u32);
+
+    assert_eq!(
+        a.try_union(&c).unwrap_err(),
+        BloomError::SizeMismatch { expected: (1_024, 4), found: (2_048, 4) }
+    );
+    assert!(a.try_intersect(&c).is_err());
+    assert!(a.contains(&1u32));
+
+    a.try_union(&b).unwrap();
+    assert!(a.contains(&1u32) && a.contains(&2u32));
+    a.try_intersect(&b).unwrap();
+    assert!(a.contains(&2u32));
+}
+
+#[test]
+fn deserializers_say_what_went_wrong() {
+    let rs = RandomState::new();
+    let legacy = BloomFilter::with_hasher(1_000, 3, rs.clone()).to_bytes();
+    assert!(BloomFilter::try_from_bytes_hasher(&legacy, rs.clone()).is_ok());
+    assert_eq!(BloomFilter::try_from_bytes_hasher(&legacy[..5], rs.clone()).unwrap_err(), BloomError::Truncated);
+    assert_eq!(BloomFilter::try_from_bytes_hasher(&legacy[8..], rs).unwrap_err(), BloomError::Corrupt);
+
+    let hasher = SeededSip13::with_seed(6);
+    let versioned = BloomFilter::with_hasher(1_000, 3, hasher).to_versioned_bytes();
+    assert!(BloomFilter::try_from_versioned_bytes_hasher(&versioned, hasher).is_ok());
+    assert_eq!(
+        BloomFilter::try_from_versioned_bytes_hasher(&versioned, SeededSip13::with_seed(7)).unwrap_err(),
+        BloomError::HasherMismatch
+    );
+    assert_eq!(
+        BloomFilter::try_from_versioned_bytes_hasher(&versioned[..100], hasher).unwrap_err(),
+        BloomError::Truncated
+    );
+    let mut corrupt = versioned.clone();
+    corrupt[70] ^= 1;
+    assert_eq!(BloomFilter::try_from_versioned_bytes_hasher(&corrupt, hasher).unwrap_err(), BloomError::BadChecksum);
+    corrupt[4] = 9;
+    assert_eq!(
+        BloomFilter::try_from_versioned_bytes_hasher(&corrupt, hasher).unwrap_err(),
+        BloomError::UnsupportedVersion(9)
+    );
+}
+
+#[test]
+fn filters_with_different_random_states_do_not_merge() {
+    let mut a = BloomFilter::new(4_096, 4);
+    let b = BloomFilter::new(4_096, 4);
+    let same = BloomFilter::with_hasher(4_096, 4, a.hasher_builder().clone());
+    println!("fingerprints: {:08x} vs {:08x}", a.hasher_fingerprint(), b.hasher_fingerprint());
+
+    assert!(!a.is_compatible_with(&b));
+    assert!(a.is_compatible_with(&same));
+    assert_eq!(a.try_union(&b).unwrap_err(), BloomError::HasherMismatch);
+    assert_eq!(a.try_intersect(&b).unwrap_err(), BloomError::HasherMismatch);
+    a.try_union(&same).unwrap();
+
+    // deterministic builders agree across instances
+    let x = BloomFilter::with_hasher(4_096, 4, SeededSip13::with_seed(1));
+    assert!(x.is_compatible_with(&BloomFilter::with_hasher(4_096, 4, SeededSip13::with_seed(1))));
+    assert!(!x.is_compatible_with(&BloomFilter::with_hasher(4_096, 4, SeededSip13::with_seed(2))));
+}
+
+#[test]
+#[should_panic(expected = "hasher mismatch for union")]
+fn union_inplace_panics_on_hasher_mismatch() {
+    let mut a = BloomFilter::new(1_024, 3);
+    a.union_inplace(&BloomFilter::new(1_024, 3));
+}
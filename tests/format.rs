@@ -0,0 +1,119 @@
+use bloomz::format::{self, HEADER_LEN};
+use bloomz::{BloomError, BloomFilter, BloomFilterRef, FilterKind, FormatError, SeededSip13};
+use std::collections::hash_map::RandomState;
+
+#[test]
+fn crc32c_reference_vector() {
+    assert_eq!(format::crc32c(b"123456789"), 0xe306_9283);
+    assert_eq!(format::crc32c(b""), 0);
+}
+
+#[test]
+fn versioned_roundtrip_keeps_metadata() {
+    let mut bf = BloomFilter::for_capacity_with_hasher(5_000, 0.01, SeededSip13::with_seed(9));
+    for i in 0..3_000u32 {
+        bf.insert(&i);
+    }
+    let bytes = bf.to_versioned_bytes();
+    println!("{} bits -> {} bytes", bf.num_bits(), bytes.len());
+
+    // the header alone is enough to inspect a file
+    let header = format::read_header(&bytes[..HEADER_LEN]).unwrap();
+    assert_eq!((header.version, header.kind), (format::VERSION, FilterKind::Bloom));
+    assert_eq!((header.hasher_id, header.seed), (1, 9));
+    assert_eq!(header.items, 3_000);
+    assert_eq!((header.capacity, header.fpr), (5_000, 0.01));
+    assert_eq!(header.total_len(), Some(bytes.len() as u64));
+
+    let restored = BloomFilter::<SeededSip13>::from_versioned_bytes(&bytes).unwrap();
+    assert_eq!(restored.approximate_items(), 3_000);
+    assert_eq!(restored.design_params(), Some((5_000, 0.01)));
+    assert!((0..3_000u32).all(|i| restored.contains(&i)));
+}
+
+#[test]
+fn corrupt_and_mismatched_data_is_rejected() {
+    let mut bf = BloomFilter::with_hasher(4_096, 3, SeededSip13::with_seed(1));
+    bf.insert(&"x");
+    let bytes = bf.to_versioned_bytes();
+
+    let mut flipped = bytes.clone();
+    flipped[HEADER_LEN + 10] ^= 0x40;
+    assert_eq!(BloomFilter::<SeededSip13>::from_versioned_bytes(&flipped).unwrap_err(), FormatError::BadChecksum);
+
+    let truncated = &bytes[..bytes.len() - 9];
+    assert_eq!(BloomFilter::<SeededSip13>::from_versioned_bytes(truncated).unwrap_err(), FormatError::Truncated);
+
+    let mut future = bytes.clone();
+    future[4] = 0xff;
+    assert_eq!(format::read_header(&future).unwrap_err(), FormatError::UnsupportedVersion(0xff));
+
+    assert_eq!(
+        BloomFilter::<RandomState>::from_versioned_bytes(&bytes).unwrap_err(),
+        FormatError::HasherMismatch { found: 1, expected: 0 }
+    );
+    assert!(BloomFilter::from_versioned_bytes_hasher(&bytes, SeededSip13::with_seed(2)).is_err());
+}
+
+#[test]
+fn oversized_payload_len_is_an_error() {
+    let hasher = SeededSip13::with_seed(1);
+    let mut bytes = BloomFilter::with_hasher(4_096, 3, hasher).to_versioned_bytes();
+    bytes[56..64].copy_from_slice(&u64::MAX.to_le_bytes());
+
+    let header = format::read_header(&bytes).unwrap();
+    assert_eq!(header.total_len(), None);
+    assert_eq!(BloomFilter::from_versioned_bytes_hasher(&bytes, hasher).unwrap_err(), FormatError::InvalidLayout);
+    assert_eq!(BloomFilterRef::<SeededSip13>::from_versioned_bytes(&bytes).unwrap_err(), FormatError::InvalidLayout);
+    assert!(BloomFilter::<SeededSip13>::read_from(&bytes[..]).is_err());
+}
+
+#[test]
+fn k_beyond_the_header_is_refused_up_front() {
+    let hasher = SeededSip13::with_seed(2);
+    let widest = BloomFilter::with_hasher(64, format::MAX_K, hasher);
+    let restored = BloomFilter::from_versioned_bytes_hasher(&widest.to_versioned_bytes(), hasher).unwrap();
+    assert_eq!(restored.num_hashes(), format::MAX_K);
+
+    assert!(matches!(BloomFilter::try_with_hasher(64, format::MAX_K + 1, hasher), Err(BloomError::InvalidParams(_))));
+    let mut legacy = BloomFilter::with_hasher(64, 3, hasher).to_bytes();
+    let k_at = legacy.len() - 4;
+    legacy[k_at..].copy_from_slice(&(format::MAX_K + 1).to_le_bytes());
+    assert!(BloomFilter::from_bytes_hasher(&legacy, hasher).is_none());
+    assert!(BloomFilterRef::from_bytes_hasher(&legacy, hasher).is_none());
+}
+
+#[test]
+fn legacy_trailer_still_loads() {
+    let rs = RandomState::new();
+    let mut bf = BloomFilter::with_hasher(1_000, 4, rs.clone());
+    for i in 0..100u32 {
+        bf.insert(&i);
+    }
+    let legacy = bf.to_bytes();
+    assert_eq!(format::read_header(&legacy).unwrap_err(), FormatError::BadMagic);
+
+    let restored = BloomFilter::from_versioned_bytes_hasher(&legacy, rs).unwrap();
+    assert!((0..100u32).all(|i| restored.contains(&i)));
+    assert_eq!(restored.num_bits(), 1_000);
+
+    // a seeded builder cannot be recovered from legacy data
+    let seeded = BloomFilter::with_hasher(1_000, 4, SeededSip13::with_seed(3)).to_bytes();
+    assert_eq!(BloomFilter::<SeededSip13>::from_versioned_bytes(&seeded).unwrap_err(), FormatError::MissingHasher);
+}
+
+#[test]
+fn fingerprint_catches_wrong_random_state() {
+    let rs = RandomState::new();
+    let mut bf = BloomFilter::with_hasher(2_048, 3, rs.clone());
+    bf.insert(&"k");
+    let bytes = bf.to_versioned_bytes();
+    let header = format::read_header(&bytes).unwrap();
+    assert_eq!(header.fingerprint, bf.hasher_fingerprint());
+
+    assert!(BloomFilter::from_versioned_bytes_hasher(&bytes, rs).unwrap().contains(&"k"));
+    assert_eq!(
+        BloomFilter::from_versioned_bytes_hasher(&bytes, RandomState::new()).unwrap_err(),
+        FormatError::FingerprintMismatch
+    );
+}
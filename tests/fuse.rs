@@ -0,0 +1,70 @@
+use bloomz::{BinaryFuse16, BinaryFuse8};
+use std::collections::hash_map::RandomState;
+
+#[test]
+fn no_false_negatives() {
+    let keys: Vec<u64> = (0..100_000).collect();
+    let filter = BinaryFuse8::from_keys_with_hasher(&keys, RandomState::new()).expect("build");
+    assert_eq!(filter.len(), keys.len());
+    for k in &keys {
+        assert!(filter.contains(k), "missing {}", k);
+    }
+}
+
+#[test]
+fn space_and_fpr_8_bit() {
+    let n = 200_000u64;
+    let keys: Vec<u64> = (0..n).collect();
+    let filter = BinaryFuse8::from_keys_with_hasher(&keys, RandomState::new()).unwrap();
+    let trials = 200_000u64;
+    let fp = (n..n + trials).filter(|i| filter.contains(i)).count();
+    let rate = fp as f64 / trials as f64;
+    println!("8-bit: {:.2} bits/key, observed FP {:.5}", filter.bits_per_entry(), rate);
+    assert!(filter.bits_per_entry() < 9.5);
+    assert!(rate < 0.006, "false positive rate too high: {}", rate);
+
+    let bloom_bits = bloomz::math::optimal_m(n as usize, 0.004) as f64 / n as f64;
+    assert!(filter.bits_per_entry() < bloom_bits);
+}
+
+#[test]
+fn sixteen_bit_fingerprints() {
+    let keys: Vec<String> = (0..50_000).map(|i| format!("blocked-host-{}", i)).collect();
+    let filter = BinaryFuse16::from_keys_with_hasher(&keys, RandomState::new()).unwrap();
+    for k in &keys {
+        assert!(filter.contains(k));
+    }
+    let fp = (0..100_000).filter(|i| filter.contains(&format!("allowed-host-{}", i))).count();
+    println!("16-bit: {:.2} bits/key, {} false positives", filter.bits_per_entry(), fp);
+    assert!(fp < 10);
+}
+
+#[test]
+fn duplicates_and_tiny_sets() {
+    let keys = vec![7u32, 7, 7, 9];
+    let filter = BinaryFuse8::from_keys_with_hasher(&keys, RandomState::new()).unwrap();
+    assert!(filter.contains(&7u32) && filter.contains(&9u32));
+
+    let empty: Vec<u32> = Vec::new();
+    let filter = BinaryFuse8::from_keys_with_hasher(&empty, RandomState::new()).unwrap();
+    assert!(filter.is_empty());
+
+    let single = BinaryFuse16::from_keys(&["only"]).unwrap();
+    assert!(single.contains(&"only"));
+}
+
+#[test]
+fn serialization_roundtrip() {
+    let rs = RandomState::new();
+    let keys: Vec<u32> = (0..10_000).collect();
+    let filter = BinaryFuse16::from_keys_with_hasher(&keys, rs.clone()).unwrap();
+    let bytes = filter.to_bytes();
+    let restored = BinaryFuse16::from_bytes_hasher(&bytes, rs.clone()).expect("deserialize");
+    assert_eq!(restored.len(), keys.len());
+    for k in &keys {
+        assert!(restored.contains(k));
+    }
+    // fingerprint width is checked
+    assert!(BinaryFuse8::from_bytes_hasher(&bytes, rs.clone()).is_none());
+    assert!(BinaryFuse16::from_bytes_hasher(&bytes[1..], rs).is_none());
+}
@@ -0,0 +1,182 @@
+use bloomz::format;
+use bloomz::hashing::{self, Hash128Builder};
+use bloomz::{AtomicBloomFilter, BloomFilter, FormatError, SeededSip13, ShardedBloomFilter, SipHasher13};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{BuildHasher, BuildHasherDefault, Hasher};
+
+/// Plain SipHash-c-d over a whole message; the 128-bit variant when `wide`.
+fn sip_reference(c: usize, d: usize, (k0, k1): (u64, u64), msg: &[u8], wide: bool) -> (u64, u64) {
+    let mut v = [
+        k0 ^ 0x736f_6d65_7073_6575,
+        k1 ^ 0x646f_7261_6e64_6f6d,
+        k0 ^ 0x6c79_6765_6e65_7261,
+        k1 ^ 0x7465_6462_7974_6573,
+    ];
+    if wide {
+        v[1] ^= 0xee;
+    }
+    let rounds = |v: &mut [u64; 4], n: usize| {
+        for _ in 0..n {
+            v[0] = v[0].wrapping_add(v[1]);
+            v[1] = v[1].rotate_left(13) ^ v[0];
+            v[0] = v[0].rotate_left(32);
+            v[2] = v[2].wrapping_add(v[3]);
+            v[3] = v[3].rotate_left(16) ^ v[2];
+            v[0] = v[0].wrapping_add(v[3]);
+            v[3] = v[3].rotate_left(21) ^ v[0];
+            v[2] = v[2].wrapping_add(v[1]);
+            v[1] = v[1].rotate_left(17) ^ v[2];
+            v[2] = v[2].rotate_left(32);
+        }
+    };
+    let full = msg.len() / 8;
+    let mut last = (msg.len() as u64) << 56;
+    for (i, &b) in msg[full * 8..].iter().enumerate() {
+        last |= (b as u64) << (8 * i);
+    }
+    let blocks = msg.chunks_exact(8).map(|c| u64::from_le_bytes(c.try_into().unwrap()));
+    for m in blocks.chain(std::iter::once(last)) {
+        v[3] ^= m;
+        rounds(&mut v, c);
+        v[0] ^= m;
+    }
+    v[2] ^= if wide { 0xee } else { 0xff };
+    rounds(&mut v, d);
+    let lo = v[0] ^ v[1] ^ v[2] ^ v[3];
+    v[1] ^= 0xdd;
+    rounds(&mut v, d);
+    (lo, v[0] ^ v[1] ^ v[2] ^ v[3])
+}
+
+#[test]
+fn sip13_128_matches_reference() {
+    // check the reference itself against the published SipHash-2-4 vectors (key 00..0f, empty input)
+    let key = (0x0706_0504_0302_0100, 0x0f0e_0d0c_0b0a_0908);
+    assert_eq!(sip_reference(2, 4, key, b"", false).0, 0x726f_db47_dd0e_0e31);
+    let v128: [u8; 16] = [0xa3, 0x81, 0x7f, 0x04, 0xba, 0x25, 0xa8, 0xe6, 0x6d, 0xf6, 0x72, 0x14, 0xc7, 0x55, 0x02, 0x93];
+    let (lo, hi) = sip_reference(2, 4, key, b"", true);
+    assert_eq!(u128::from(hi) << 64 | u128::from(lo), u128::from_le_bytes(v128));
+
+    let data: Vec<u8> = (0..64).collect();
+    for len in 0..data.len() {
+        let (lo, hi) = sip_reference(1, 3, key, &data[..len], true);
+        let mut ours = SipHasher13::new_with_keys_128(key.0, key.1);
+        // split writes exercise the tail buffer
+        let (a, b) = data[..len].split_at(len / 3);
+        ours.write(a);
+        ours.write(b);
+        assert_eq!(ours.finish128(), u128::from(hi) << 64 | u128::from(lo), "length {}", len);
+    }
+}
+
+#[cfg(feature = "fast-xxh3")]
+#[test]
+fn xxh3_128_matches_reference() {
+    use bloomz::SeededXxh3;
+    let b = SeededXxh3::with_seed(99);
+    assert_eq!(b.hash_one_128(&5u32), xxhash_rust::xxh3::xxh3_128_with_seed(&5u32.to_le_bytes(), 99));
+    let digest = xxhash_rust::xxh3::xxh3_128_with_seed(b"hello world", 99);
+    let mut h = b.build_hasher();
+    h.write(b"hello world");
+    assert_eq!(h.finish128(), digest);
+}
+
+#[test]
+fn crate_builders_hash_in_a_single_pass() {
+    let digest = SeededSip13::with_seed(1).hash_one_128(&1u8);
+    assert_eq!(hashing::split128(digest), (digest as u64, (digest >> 64) as u64 | 1));
+
+    let n = 20_000u64;
+    let mut fast = BloomFilter::for_capacity_with_hasher_128(n as usize, 0.01, SeededSip13::with_seed(4));
+    let mut plain = BloomFilter::for_capacity_with_hasher(n as usize, 0.01, BuildHasherDefault::<DefaultHasher>::default());
+    for i in 0..n {
+        let key = format!("user-{:08}@example.com", i);
+        fast.insert(&key);
+        plain.insert(&key);
+    }
+    let fpr = |bf: &dyn Fn(&String) -> bool| (n..n + 100_000).filter(|i| bf(&format!("user-{:08}@example.com", i))).count() as f64 / 100_000.0;
+    let (fast_fpr, plain_fpr) = (fpr(&|k| fast.contains(k)), fpr(&|k| plain.contains(k)));
+    println!("fpr: single pass {:.4}, two pass {:.4}", fast_fpr, plain_fpr);
+    assert!(fast_fpr < 0.015 && plain_fpr < 0.015);
+
+    assert!(format::read_header(&fast.to_versioned_bytes()).unwrap().hash128);
+    assert!(!format::read_header(&plain.to_versioned_bytes()).unwrap().hash128);
+    let restored = BloomFilter::<SeededSip13>::from_versioned_bytes(&fast.to_versioned_bytes()).unwrap();
+    assert!(restored.is_compatible_with(&fast));
+    // `with_hasher` picks the digest on its own, and legacy bytes read it back the same way
+    let auto = BloomFilter::for_capacity_with_hasher(n as usize, 0.01, SeededSip13::with_seed(4));
+    assert!(auto.is_compatible_with(&fast));
+    assert!(format::read_header(&auto.to_versioned_bytes()).unwrap().hash128);
+    let legacy = BloomFilter::from_bytes_hasher(&fast.to_bytes(), SeededSip13::with_seed(4)).unwrap();
+    assert!((0..n).all(|i| legacy.contains(&format!("user-{:08}@example.com", i))));
+}
+
+/// A builder with nothing but `BuildHasher`: no `Default`, no 128-bit digest.
+#[derive(Clone)]
+struct Keyed(u64, u64);
+
+/// A third-party 128-bit builder, which filters only use when asked to.
+#[derive(Clone)]
+struct Keyed128(u64, u64);
+
+impl BuildHasher for Keyed128 {
+    type Hasher = SipHasher13;
+
+    fn build_hasher(&self) -> SipHasher13 {
+        SipHasher13::new_with_keys_128(self.0, self.1)
+    }
+}
+
+impl Hash128Builder for Keyed128 {
+    fn hash_one_128<T: std::hash::Hash + ?Sized>(&self, item: &T) -> u128 {
+        let mut h = self.build_hasher();
+        item.hash(&mut h);
+        h.finish128()
+    }
+}
+
+impl BuildHasher for Keyed {
+    type Hasher = SipHasher13;
+
+    fn build_hasher(&self) -> SipHasher13 {
+        SipHasher13::new_with_keys(self.0, self.1)
+    }
+}
+
+#[test]
+fn plain_build_hasher_works_everywhere() {
+    let mut bf = BloomFilter::with_hasher(10_000, 4, Keyed(1, 2));
+    let sharded = ShardedBloomFilter::with_hasher(4, 10_000, 4, Keyed(1, 2));
+    for i in 0..500u32 {
+        bf.insert(&i);
+        sharded.insert(&i);
+    }
+    let atomic = AtomicBloomFilter::from(bf.clone());
+    assert!((0..500u32).all(|i| bf.contains(&i) && atomic.contains(&i) && sharded.contains(&i)));
+    let hash = bf.hash_key(&7u32);
+    assert!(atomic.contains_hashed(hash) && sharded.contains_hashed(hash));
+}
+
+#[test]
+fn single_pass_flag_needs_a_128_bit_builder() {
+    let bf = BloomFilter::with_hasher(4_096, 3, BuildHasherDefault::<DefaultHasher>::default());
+    let mut bytes = bf.to_versioned_bytes();
+    bytes[27] |= 0x80;
+    let body = bytes.len() - 4;
+    let crc = format::crc32c(&bytes[..body]);
+    bytes[body..].copy_from_slice(&crc.to_le_bytes());
+    assert!(format::read_header(&bytes).unwrap().hash128);
+    assert_eq!(
+        BloomFilter::<BuildHasherDefault<DefaultHasher>>::from_versioned_bytes(&bytes).unwrap_err(),
+        FormatError::FingerprintMismatch
+    );
+}
+
+#[test]
+#[should_panic(expected = "use to_versioned_bytes")]
+fn legacy_bytes_refuse_single_pass_filters() {
+    assert!(!BloomFilter::with_hasher(4_096, 3, Keyed128(5, 6)).is_compatible_with(&BloomFilter::with_hasher_128(4_096, 3, Keyed128(5, 6))));
+    let atomic = AtomicBloomFilter::from(BloomFilter::with_hasher_128(4_096, 3, Keyed128(5, 6)));
+    atomic.insert(&1u32);
+    let _ = atomic.to_bytes();
+}
@@ -0,0 +1,121 @@
+use bloomz::format;
+use bloomz::{BloomError, BloomFilter, BloomFilterRef, IndexStrategy, SeededSip13};
+use std::collections::hash_map::RandomState;
+
+const ALL: [IndexStrategy; 5] = [
+    IndexStrategy::DoubleHashing,
+    IndexStrategy::FastRange,
+    IndexStrategy::EnhancedDoubleHashing,
+    IndexStrategy::TripleHashing,
+    IndexStrategy::Independent,
+];
+
+#[test]
+fn empirical_fpr_per_strategy() {
+    // 16 bits/key with k = 11: textbook FPR (1 - e^(-k/16))^k ~ 0.046%
+    let n = 10_000u64;
+    let (m, k) = (16 * n as usize, 11);
+    let expected = (1.0 - (-(k as f64) * n as f64 / m as f64).exp()).powi(k as i32);
+    let queries = 400_000u64;
+    let rs = RandomState::new();
+
+    for strategy in ALL {
+        let mut bf = BloomFilter::with_hasher_and_strategy(m, k, rs.clone(), strategy);
+        for i in 0..n {
+            bf.insert(&i);
+        }
+        assert!((0..n).all(|i| bf.contains(&i)), "{:?} lost an item", strategy);
+        let fp = (n..n + queries).filter(|i| bf.contains(i)).count();
+        let fpr = fp as f64 / queries as f64;
+        println!("{:?}: fpr {:.5} (expected {:.5})", strategy, fpr, expected);
+        assert!(fpr < expected * 2.0, "{:?} fpr {} too high", strategy, fpr);
+    }
+}
+
+#[test]
+fn strategies_place_bits_differently_but_stay_in_range() {
+    let rs = RandomState::new();
+    // m not a power of two and smaller than one word for the reducers' edge cases
+    for m in [1, 63, 1_000_003] {
+        for strategy in ALL {
+            let mut bf = BloomFilter::with_hasher_and_strategy(m, 5, rs.clone(), strategy);
+            for i in 0..200u32 {
+                bf.insert(&i);
+            }
+            assert!((0..200u32).all(|i| bf.contains(&i)));
+        }
+    }
+
+    let mut a = BloomFilter::with_hasher_and_strategy(4_096, 4, rs.clone(), IndexStrategy::FastRange);
+    let b = BloomFilter::with_hasher(4_096, 4, rs);
+    assert_eq!(b.index_strategy(), IndexStrategy::DoubleHashing);
+    assert!(!a.is_compatible_with(&b));
+    assert_eq!(a.try_union(&b).unwrap_err(), BloomError::StrategyMismatch);
+}
+
+#[test]
+fn enhanced_and_triple_probes_differ_from_fastrange() {
+    // FastRange probes h1 + i*h2; the other two share its first two probes
+    // and then drift away by the (i^3 - i)/6 or i*(i-1)/2 * z terms
+    for m in [1_000_003, 1 << 24] {
+        let mut bf = BloomFilter::with_hasher_and_strategy(m, 8, SeededSip13::with_seed(3), IndexStrategy::FastRange);
+        for i in 0..1_000u32 {
+            bf.insert(&i);
+        }
+        let bytes = bf.to_versioned_bytes();
+        for strategy in [IndexStrategy::EnhancedDoubleHashing, IndexStrategy::TripleHashing] {
+            // the same bits read back under the other strategy
+            let mut relabeled = bytes.clone();
+            relabeled[27] = strategy.id();
+            let body = relabeled.len() - format::CHECKSUM_LEN;
+            let crc = format::crc32c(&relabeled[..body]);
+            relabeled[body..].copy_from_slice(&crc.to_le_bytes());
+            let view = BloomFilterRef::<SeededSip13>::from_versioned_bytes(&relabeled).unwrap();
+            let found = (0..1_000u32).filter(|i| view.contains(i)).count();
+            println!("{:?} at m = {}: {} of 1000 FastRange keys found", strategy, m, found);
+            // a strategy probing like FastRange would find all of them
+            assert!(found < 10, "{:?} probes like FastRange at m = {}", strategy, m);
+        }
+    }
+}
+
+#[test]
+fn strategy_is_recorded_in_versioned_files() {
+    for strategy in ALL {
+        let mut bf = BloomFilter::with_hasher_and_strategy(10_000, 6, SeededSip13::with_seed(2), strategy);
+        for i in 0..500u32 {
+            bf.insert(&i);
+        }
+        let bytes = bf.to_versioned_bytes();
+        assert_eq!(format::read_header(&bytes).unwrap().strategy, strategy);
+
+        let restored = BloomFilter::<SeededSip13>::from_versioned_bytes(&bytes).unwrap();
+        assert_eq!(restored.index_strategy(), strategy);
+        assert!((0..500u32).all(|i| restored.contains(&i)));
+        let view = BloomFilterRef::<SeededSip13>::from_versioned_bytes(&bytes).unwrap();
+        assert!((0..500u32).all(|i| view.contains(&i)));
+    }
+}
+
+#[test]
+#[should_panic(expected = "legacy layout only holds DoubleHashing")]
+fn legacy_bytes_refuse_other_strategies() {
+    let mut bf = BloomFilter::with_hasher_and_strategy(10_000, 6, SeededSip13::with_seed(2), IndexStrategy::FastRange);
+    bf.insert(&1u32);
+    // would read back as DoubleHashing and miss every key
+    let _ = bf.to_bytes();
+}
+
+#[test]
+fn try_to_bytes_reports_other_strategies() {
+    let hasher = SeededSip13::with_seed(2);
+    for strategy in [IndexStrategy::FastRange, IndexStrategy::EnhancedDoubleHashing, IndexStrategy::TripleHashing] {
+        let bf = BloomFilter::with_hasher_and_strategy(10_000, 6, hasher, strategy);
+        assert_eq!(bf.try_to_bytes().unwrap_err(), BloomError::StrategyMismatch);
+    }
+    let mut bf = BloomFilter::with_hasher(10_000, 6, hasher);
+    bf.insert(&1u32);
+    let bytes = bf.try_to_bytes().unwrap();
+    assert_eq!(bytes, bf.to_bytes());
+    assert!(BloomFilter::from_bytes_hasher(&bytes, hasher).unwrap().contains(&1u32));
+}
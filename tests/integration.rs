@@ -136,3 +136,26 @@ fn test_parallel_operations() {
     
     println!("Parallel operations test passed! Found {}/500 expected items", found_expected);
 }
+
+#[test]
+#[cfg(feature = "rayon")]
+fn test_parallel_batch_matches_sequential() {
+    use rayon::prelude::*;
+
+    let rs = RandomState::new();
+    let items: Vec<u64> = (0..50_000).collect();
+
+    let mut sequential = BloomFilter::with_hasher(500_000, 7, rs.clone());
+    for item in &items {
+        sequential.insert(item);
+    }
+    let mut parallel = BloomFilter::with_hasher(500_000, 7, rs.clone());
+    parallel.insert_batch(items.par_iter().cloned());
+
+    assert_eq!(parallel.to_bytes(), sequential.to_bytes());
+    assert_eq!(parallel.approximate_items(), items.len());
+
+    // duplicates count like repeated `insert` calls
+    parallel.insert_batch(items[..100].par_iter().cloned());
+    assert_eq!(parallel.approximate_items(), items.len() + 100);
+}
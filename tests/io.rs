@@ -0,0 +1,89 @@
+use bloomz::bitset::BitSet;
+use bloomz::{BloomFilter, FormatError, SeededSip13};
+use std::io::{Cursor, ErrorKind, Write};
+
+/// Writer that records the largest single write it sees.
+struct Recorder {
+    bytes: Vec<u8>,
+    largest: usize,
+}
+
+impl Write for Recorder {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.largest = self.largest.max(buf.len());
+        self.bytes.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn stream_matches_versioned_bytes_with_bounded_writes() {
+    let mut bf = BloomFilter::for_capacity_with_hasher(200_000, 0.01, SeededSip13::with_seed(4));
+    for i in 0..200_000u32 {
+        bf.insert(&i);
+    }
+    let mut rec = Recorder { bytes: Vec::new(), largest: 0 };
+    bf.write_to(&mut rec).unwrap();
+    println!("{} bytes written, largest write {}", rec.bytes.len(), rec.largest);
+    assert_eq!(rec.bytes, bf.to_versioned_bytes());
+    assert!(rec.largest <= 64 * 1024);
+
+    // a second value after the filter is left unread
+    rec.bytes.extend_from_slice(b"next");
+    let mut cursor = Cursor::new(&rec.bytes);
+    let restored = BloomFilter::<SeededSip13>::read_from(&mut cursor).unwrap();
+    assert_eq!(restored.approximate_items(), 200_000);
+    assert!((0..200_000u32).all(|i| restored.contains(&i)));
+    assert_eq!(&rec.bytes[cursor.position() as usize..], b"next");
+}
+
+#[test]
+fn truncated_and_corrupt_streams_fail() {
+    let mut bf = BloomFilter::with_hasher(10_000, 4, SeededSip13::with_seed(1));
+    bf.insert(&"a");
+    let bytes = bf.to_versioned_bytes();
+
+    for cut in [10, 64, 500, bytes.len() - 1] {
+        let err = BloomFilter::<SeededSip13>::read_from(&bytes[..cut]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof, "cut at {}", cut);
+    }
+
+    let mut corrupt = bytes.clone();
+    corrupt[200] ^= 0x10;
+    let err = BloomFilter::<SeededSip13>::read_from(&corrupt[..]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    assert_eq!(err.into_inner().unwrap().downcast_ref::<FormatError>(), Some(&FormatError::BadChecksum));
+
+    let err = BloomFilter::read_from_hasher(&bytes[..], SeededSip13::with_seed(2)).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    assert_eq!(BloomFilter::<SeededSip13>::read_from(&bf.to_bytes()[..]).unwrap_err().kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn bitset_roundtrip() {
+    let mut bits = BitSet::new(100_003);
+    for i in (0..100_003).step_by(7) {
+        bits.set(i);
+    }
+    let mut out = Vec::new();
+    bits.write_to(&mut out).unwrap();
+    assert_eq!(out.len(), 8 + 100_003usize.div_ceil(64) * 8);
+
+    let back = BitSet::read_from(&out[..]).unwrap();
+    assert_eq!(back.len_bits(), 100_003);
+    assert_eq!(back.words_slice(), bits.words_slice());
+
+    assert_eq!(BitSet::read_from(&out[..out.len() - 3]).err().map(|e| e.kind()), Some(ErrorKind::UnexpectedEof));
+    // a corrupt length claiming petabytes runs out of input, not memory
+    let mut huge = out.clone();
+    huge[..8].copy_from_slice(&(1u64 << 53).to_le_bytes());
+    assert_eq!(BitSet::read_from(&huge[..]).err().map(|e| e.kind()), Some(ErrorKind::UnexpectedEof));
+    // padding bits past the length mean the data is corrupt
+    let last = out.len() - 1;
+    out[last] = 0x80;
+    assert_eq!(BitSet::read_from(&out[..]).err().map(|e| e.kind()), Some(ErrorKind::InvalidData));
+}
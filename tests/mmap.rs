@@ -0,0 +1,103 @@
+#![cfg(all(feature = "mmap", target_os = "linux"))]
+
+use bloomz::{BloomFilter, FormatError, MmapBloomFilter, MmapBloomFilterMut, SeededSip13};
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("bloomz-{}-{}.blmz", name, std::process::id()))
+}
+
+#[test]
+fn create_insert_flush_reopen() {
+    let path = temp_path("rw");
+    {
+        let mut bf = MmapBloomFilterMut::create_for_capacity(&path, 50_000, 0.01, SeededSip13::with_seed(11)).unwrap();
+        for i in 0..50_000u64 {
+            bf.insert(&i);
+        }
+        assert!((0..50_000u64).all(|i| bf.contains(&i)));
+        // the on-disk checksum is stale until `flush`
+        assert_eq!(bf.verify(), Err(FormatError::BadChecksum));
+        bf.flush().unwrap();
+        bf.verify().unwrap();
+    }
+
+    let ro = MmapBloomFilter::<SeededSip13>::open(&path).unwrap();
+    ro.verify().unwrap();
+    assert_eq!(ro.approximate_items(), 50_000);
+    assert!((0..50_000u64).all(|i| ro.contains(&i)));
+    let fp = (50_000..150_000u64).filter(|i| ro.contains(i)).count();
+    println!("mmap fpr: {:.4}", fp as f64 / 100_000.0);
+    assert!(fp < 1_500);
+
+    // the file is the regular versioned layout
+    let owned = BloomFilter::<SeededSip13>::from_versioned_bytes(&std::fs::read(&path).unwrap()).unwrap();
+    assert!((0..50_000u64).all(|i| owned.contains(&i)));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn opens_files_written_by_bloom_filter() {
+    let path = temp_path("ro");
+    let mut bf = BloomFilter::with_hasher(100_000, 5, SeededSip13::with_seed(3));
+    for i in 0..5_000u32 {
+        bf.insert(&i);
+    }
+    std::fs::write(&path, bf.to_versioned_bytes()).unwrap();
+
+    let mut rw = MmapBloomFilterMut::<SeededSip13>::open(&path).unwrap();
+    assert_eq!((rw.num_bits(), rw.num_hashes(), rw.approximate_items()), (100_000, 5, 5_000));
+    rw.insert(&"late");
+    rw.flush().unwrap();
+    drop(rw);
+
+    let ro = MmapBloomFilter::open_hasher(&path, SeededSip13::with_seed(3)).unwrap();
+    assert!(ro.contains(&"late") && (0..5_000u32).all(|i| ro.contains(&i)));
+    assert_eq!(ro.approximate_items(), 5_001);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn rejects_bad_files() {
+    let path = temp_path("bad");
+    let bytes = BloomFilter::with_hasher(4_096, 3, SeededSip13::with_seed(1)).to_versioned_bytes();
+
+    std::fs::write(&path, &bytes[..bytes.len() - 100]).unwrap();
+    assert_eq!(MmapBloomFilter::<SeededSip13>::open(&path).unwrap_err().kind(), ErrorKind::UnexpectedEof);
+
+    std::fs::write(&path, &bytes).unwrap();
+    let err = MmapBloomFilter::open_hasher(&path, SeededSip13::with_seed(2)).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+    // a payload length that overflows the file size is rejected, not a panic
+    let mut huge = bytes.clone();
+    huge[56..64].copy_from_slice(&u64::MAX.to_le_bytes());
+    std::fs::write(&path, &huge).unwrap();
+    let err = MmapBloomFilter::<SeededSip13>::open(&path).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+    // corruption is only found by the explicit checksum pass
+    let mut corrupt = bytes.clone();
+    corrupt[100] ^= 1;
+    std::fs::write(&path, &corrupt).unwrap();
+    let ro = MmapBloomFilter::<SeededSip13>::open(&path).unwrap();
+    assert_eq!(ro.verify(), Err(FormatError::BadChecksum));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn bad_parameters_are_io_errors() {
+    let path = temp_path("params");
+    let hasher = SeededSip13::with_seed(1);
+    let errors = [
+        MmapBloomFilterMut::create(&path, 0, 3, hasher).unwrap_err(),
+        MmapBloomFilterMut::create(&path, 4_096, 0, hasher).unwrap_err(),
+        MmapBloomFilterMut::create(&path, 4_096, bloomz::format::MAX_K + 1, hasher).unwrap_err(),
+        MmapBloomFilterMut::create_for_capacity(&path, 0, 0.01, hasher).unwrap_err(),
+        MmapBloomFilterMut::create_for_capacity(&path, 1_000, 1.0, hasher).unwrap_err(),
+    ];
+    assert!(errors.iter().all(|e| e.kind() == ErrorKind::InvalidInput));
+    // nothing was created
+    assert!(!path.exists());
+}
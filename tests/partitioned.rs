@@ -0,0 +1,68 @@
+use bloomz::PartitionedBloomFilter;
+use std::collections::hash_map::RandomState;
+
+#[test]
+fn one_bit_per_slice() {
+    let mut pbf = PartitionedBloomFilter::with_hasher(10_000, 5, RandomState::new());
+    assert_eq!(pbf.num_bits(), pbf.slice_bits() * 5);
+    assert_eq!(pbf.slice_bits() % 64, 0);
+
+    pbf.insert(&"hello");
+    assert!(pbf.contains(&"hello"));
+    assert_eq!(pbf.slice_ones(), vec![1; 5]);
+}
+
+#[test]
+fn fill_stats_and_fpr() {
+    let n = 5_000usize;
+    let p = 0.01;
+    let m = bloomz::math::optimal_m(n, p);
+    let k = bloomz::math::optimal_k(m, n);
+    let mut pbf = PartitionedBloomFilter::with_hasher(m, k, RandomState::new());
+    for i in 0..n as u64 {
+        pbf.insert(&i);
+    }
+    let fills = pbf.slice_fill_ratios();
+    println!("slice fills: {:?}", fills);
+    for f in &fills {
+        assert!((0.3..0.7).contains(f), "unbalanced slice fill {}", f);
+    }
+    assert!((pbf.current_fpr() - pbf.estimated_fpr()).abs() < 0.005);
+
+    let trials = 10_000u64;
+    let fp = (n as u64..n as u64 + trials).filter(|i| pbf.contains(i)).count();
+    let rate = fp as f64 / trials as f64;
+    println!("Observed FP: {:.4}, expected {:.4}", rate, pbf.estimated_fpr());
+    assert!(rate <= p * 3.0, "false positive rate too high: {}", rate);
+}
+
+#[test]
+fn union_intersection_and_roundtrip() {
+    let rs = RandomState::new();
+    let mut a = PartitionedBloomFilter::with_hasher(2_000, 4, rs.clone());
+    let mut b = PartitionedBloomFilter::with_hasher(2_000, 4, rs.clone());
+    for i in 0..300u32 {
+        a.insert(&i);
+    }
+    for i in 200..500u32 {
+        b.insert(&i);
+    }
+    let mut inter = a.clone();
+    inter.intersect_inplace(&b);
+    assert!(inter.contains(&250u32));
+
+    a.union_inplace(&b);
+    let restored = PartitionedBloomFilter::from_bytes_hasher(&a.to_bytes(), rs.clone()).expect("deserialize");
+    assert_eq!(restored.slice_bits(), a.slice_bits());
+    for i in 0..500u32 {
+        assert!(restored.contains(&i), "missing {}", i);
+    }
+
+    // neither layout is read as the other
+    let plain = bloomz::BloomFilter::with_hasher(1_024, 4, rs.clone());
+    assert!(PartitionedBloomFilter::from_bytes_hasher(&plain.to_bytes(), rs.clone()).is_none());
+    let bytes = PartitionedBloomFilter::with_hasher(1_024, 4, rs.clone()).to_bytes();
+    assert!(bloomz::BloomFilter::from_bytes_hasher(&bytes, rs.clone()).is_none());
+    assert!(bloomz::BloomFilter::from_versioned_bytes_hasher(&bytes, rs.clone()).is_err());
+    assert!(bloomz::BloomFilterRef::from_bytes_hasher(&bytes, rs).is_none());
+}
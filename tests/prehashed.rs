@@ -0,0 +1,69 @@
+use bloomz::{AtomicBloomFilter, BloomFilter, IndexStrategy, SeededSip13, ShardedBloomFilter};
+use std::collections::hash_map::RandomState;
+
+#[test]
+fn one_hash_probes_many_filters() {
+    let builder = SeededSip13::with_seed(5);
+    let strategies = [
+        IndexStrategy::DoubleHashing,
+        IndexStrategy::FastRange,
+        IndexStrategy::EnhancedDoubleHashing,
+        IndexStrategy::TripleHashing,
+    ];
+    // partitions of different sizes and strategies, all sharing the builder
+    let mut parts: Vec<BloomFilter<SeededSip13>> = (0..24)
+        .map(|i| BloomFilter::with_hasher_and_strategy(8_000 + 997 * i, 4 + i as u32 % 5, builder, strategies[i % 3]))
+        .collect();
+
+    for key in 0..10_000u64 {
+        let hash = parts[0].hash_key(&key);
+        parts[key as usize % 24].insert_hashed(hash);
+    }
+    let mut hits = 0;
+    for key in 0..20_000u64 {
+        let hash = parts[0].hash_key(&key);
+        for part in &parts {
+            assert_eq!(part.contains_hashed(hash), part.contains(&key));
+            hits += part.contains_hashed(hash) as usize;
+        }
+        if key < 10_000 {
+            assert!(parts[key as usize % 24].contains(&key));
+        }
+    }
+    println!("hits across partitions: {}", hits);
+    assert_eq!(parts.iter().map(|p| p.approximate_items()).sum::<usize>(), 10_000);
+}
+
+#[test]
+fn hashes_are_shared_with_atomic_and_sharded_filters() {
+    let rs = RandomState::new();
+    let plain = BloomFilter::with_hasher(50_000, 6, rs.clone());
+    let atomic = AtomicBloomFilter::with_hasher(50_000, 6, rs.clone());
+    let sharded = ShardedBloomFilter::with_hasher(4, 20_000, 6, rs);
+
+    for key in 0..2_000u32 {
+        let hash = plain.hash_key(&key);
+        atomic.insert_hashed(hash);
+        sharded.insert_hashed(hash);
+        assert_eq!(hash, sharded.hash_key(&key));
+    }
+    assert!((0..2_000u32).all(|k| atomic.contains(&k) && sharded.contains(&k)));
+    assert!((0..2_000u32).all(|k| sharded.contains_hashed(atomic.hash_key(&k))));
+    assert_eq!(sharded.approximate_items(), 2_000);
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "incompatible hasher builder")]
+fn hash_from_another_builder_is_caught() {
+    let a = BloomFilter::with_hasher(1_000, 3, RandomState::new());
+    let mut b = BloomFilter::with_hasher(1_000, 3, RandomState::new());
+    b.insert_hashed(a.hash_key(&"key"));
+}
+
+#[test]
+#[should_panic(expected = "cannot use pre-hashed keys")]
+fn independent_probes_need_the_item() {
+    let bf = BloomFilter::with_hasher_and_strategy(1_000, 3, SeededSip13::with_seed(1), IndexStrategy::Independent);
+    bf.contains_hashed(bf.hash_key(&"key"));
+}
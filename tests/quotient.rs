@@ -0,0 +1,134 @@
+use bloomz::{QuotientError, QuotientFilter};
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+
+#[test]
+fn insert_contains_remove() {
+    let mut qf = QuotientFilter::with_hasher(10, 20, RandomState::new());
+    for i in 0..700u32 {
+        qf.insert(&i).unwrap();
+    }
+    assert_eq!(qf.len(), 700);
+    for i in 0..700u32 {
+        assert!(qf.contains(&i), "missing {}", i);
+    }
+    for i in (0..700u32).step_by(2) {
+        assert!(qf.remove(&i), "remove failed for {}", i);
+    }
+    assert_eq!(qf.len(), 350);
+    for i in 0..700u32 {
+        assert_eq!(qf.contains(&i), i % 2 == 1, "wrong answer for {}", i);
+    }
+    assert!(!qf.remove(&"never-inserted"));
+}
+
+#[test]
+fn randomized_against_model_at_high_load() {
+    // small table, wide remainders: fingerprint collisions are negligible, so
+    // the filter must agree exactly with a multiset of the live keys
+    let mut qf = QuotientFilter::with_hasher(8, 24, RandomState::new());
+    let mut model: HashMap<u64, usize> = HashMap::new();
+    let mut state = 0x1234_5678_9abc_def0u64;
+    for _ in 0..20_000 {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        let key = state % 400;
+        if state >> 60 < 9 && qf.len() < 240 {
+            qf.insert(&key).unwrap();
+            *model.entry(key).or_default() += 1;
+        } else if let Some(copies) = model.get_mut(&key) {
+            assert!(qf.remove(&key));
+            *copies -= 1;
+            if *copies == 0 {
+                model.remove(&key);
+            }
+        }
+        assert_eq!(qf.len(), model.values().sum::<usize>());
+    }
+    for key in 0..400u64 {
+        assert_eq!(qf.contains(&key), model.contains_key(&key), "disagree on {}", key);
+    }
+}
+
+#[test]
+fn full_and_grow() {
+    let mut qf = QuotientFilter::with_hasher(4, 12, RandomState::new());
+    let mut n = 0u32;
+    while qf.insert(&n) != Err(QuotientError::Full) {
+        n += 1;
+    }
+    assert_eq!(qf.len(), 16);
+    // a repeated item needs a slot of its own
+    assert_eq!(qf.insert(&0u32), Err(QuotientError::Full));
+
+    qf.grow().unwrap();
+    assert_eq!((qf.quotient_bits(), qf.remainder_bits()), (5, 11));
+    assert_eq!(qf.len(), 16);
+    for i in 0..n {
+        assert!(qf.contains(&i), "lost {} while growing", i);
+    }
+    qf.insert(&n).unwrap();
+
+    let mut tiny = QuotientFilter::with_hasher(3, 1, RandomState::new());
+    assert_eq!(tiny.grow(), Err(QuotientError::RemainderExhausted));
+}
+
+#[test]
+fn colliding_fingerprints_keep_their_own_entries() {
+    // 6-bit fingerprints: some of the first keys are bound to collide
+    let mut qf = QuotientFilter::with_hasher(4, 2, RandomState::new());
+    qf.insert(&0u32).unwrap();
+    // with one entry stored, only a colliding key is found
+    let twin = (1..1_000u32).find(|i| qf.contains(i)).unwrap();
+    qf.insert(&twin).unwrap();
+    qf.insert(&7_777u32).unwrap();
+    qf.insert(&7_777u32).unwrap();
+    assert_eq!(qf.len(), 4);
+
+    assert!(qf.remove(&0u32));
+    assert!(qf.contains(&twin), "removing a colliding key dropped its twin");
+    assert!(qf.remove(&7_777u32));
+    assert!(qf.contains(&7_777u32), "one remove dropped both copies");
+    assert!(qf.remove(&twin) && qf.remove(&7_777u32));
+    assert!(qf.is_empty() && !qf.contains(&twin));
+}
+
+#[test]
+fn merge_without_keys() {
+    let rs = RandomState::new();
+    let mut a = QuotientFilter::with_hasher(8, 16, rs.clone());
+    let mut b = QuotientFilter::with_hasher(9, 15, rs.clone());
+    for i in 0..150u32 {
+        a.insert(&i).unwrap();
+    }
+    for i in 100..400u32 {
+        b.insert(&i).unwrap();
+    }
+    let merged = a.merge(&b).unwrap();
+    assert!(merged.load_factor() <= bloomz::quotient::TARGET_LOAD);
+    // 100..150 is in both inputs, so it is stored twice
+    assert_eq!(merged.len(), 450);
+    for i in 0..400u32 {
+        assert!(merged.contains(&i), "missing {}", i);
+    }
+
+    let c = QuotientFilter::with_hasher(8, 10, rs);
+    assert_eq!(a.merge(&c).err(), Some(QuotientError::Incompatible));
+}
+
+#[test]
+fn sized_for_target_fpr() {
+    let p = 0.01;
+    let n = 5_000u64;
+    let mut qf = QuotientFilter::new_for_capacity(n as usize, p);
+    assert_eq!(qf.remainder_bits(), bloomz::math::quotient_remainder_bits(p));
+    for i in 0..n {
+        qf.insert(&i).unwrap();
+    }
+    let trials = 20_000u64;
+    let fp = (n..n + trials).filter(|i| qf.contains(i)).count();
+    let rate = fp as f64 / trials as f64;
+    println!("Observed FP {:.4}, expected {:.4}", rate, qf.estimated_fpr());
+    assert!(rate <= p, "false positive rate too high: {}", rate);
+}
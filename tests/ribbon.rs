@@ -0,0 +1,72 @@
+use bloomz::{math, RibbonFilter};
+use std::collections::hash_map::RandomState;
+
+#[test]
+fn no_false_negatives() {
+    let filter = RibbonFilter::from_keys_with_hasher(0..100_000u64, 0.01, RandomState::new()).expect("build");
+    assert_eq!(filter.len(), 100_000);
+    for k in 0..100_000u64 {
+        assert!(filter.contains(&k), "missing {}", k);
+    }
+}
+
+#[test]
+fn fpr_tracks_target_including_fractional_bits() {
+    let n = 100_000u64;
+    let trials = 400_000u64;
+    for p in [0.01, 0.004, 0.001] {
+        let filter = RibbonFilter::from_keys_with_hasher(0..n, p, RandomState::new()).unwrap();
+        let fp = (n..n + trials).filter(|i| filter.contains(i)).count();
+        let rate = fp as f64 / trials as f64;
+        println!(
+            "p={}: {:.2} result bits, {:.2} bits/key, expected {:.5}, observed {:.5}",
+            p,
+            filter.result_bits(),
+            filter.bits_per_entry(),
+            filter.estimated_fpr(),
+            rate
+        );
+        assert!((filter.estimated_fpr() - p).abs() < p * 0.05);
+        assert!(rate < p * 1.3, "false positive rate too high: {} for target {}", rate, p);
+    }
+}
+
+#[test]
+fn smaller_than_bloom() {
+    let n = 200_000usize;
+    let p = 0.005;
+    let filter = RibbonFilter::from_keys_with_hasher(0..n as u64, p, RandomState::new()).unwrap();
+    let bloom_bits = math::optimal_m(n, p) as f64 / n as f64;
+    let predicted = math::ribbon_bits_per_key(n, p);
+    println!("ribbon {:.2} bits/key (predicted {:.2}), bloom {:.2}", filter.bits_per_entry(), predicted, bloom_bits);
+    // construction may add a few percent of slots on an unlucky seed
+    assert!(filter.bits_per_entry() > predicted * 0.99 && filter.bits_per_entry() < predicted * 1.05);
+    assert!(filter.bits_per_entry() < bloom_bits * 0.8);
+}
+
+#[test]
+fn duplicates_and_tiny_sets() {
+    let filter = RibbonFilter::from_keys_with_hasher(["a", "b", "a", "a"], 0.01, RandomState::new()).unwrap();
+    assert!(filter.contains(&"a") && filter.contains(&"b"));
+
+    let empty = RibbonFilter::from_keys(std::iter::empty::<u32>(), 0.01).unwrap();
+    assert!(empty.is_empty());
+    let fp = (0..10_000u32).filter(|i| empty.contains(i)).count();
+    assert!(fp < 300, "empty filter matched {} keys", fp);
+}
+
+#[test]
+fn serialization_roundtrip() {
+    let rs = RandomState::new();
+    let keys: Vec<String> = (0..20_000).map(|i| format!("key-{}", i)).collect();
+    let filter = RibbonFilter::from_keys_with_hasher(&keys, 0.003, rs.clone()).unwrap();
+    let bytes = filter.to_bytes();
+    let restored = RibbonFilter::from_bytes_hasher(&bytes, rs.clone()).expect("deserialize");
+    assert_eq!(restored.len(), keys.len());
+    assert_eq!(restored.num_slots(), filter.num_slots());
+    for k in &keys {
+        assert!(restored.contains(k));
+    }
+    assert!(RibbonFilter::from_bytes_hasher(&bytes[8..], rs.clone()).is_none());
+    assert!(RibbonFilter::from_bytes_hasher(&bytes[..20], rs).is_none());
+}
@@ -0,0 +1,81 @@
+use bloomz::ScalableBloomFilter;
+use std::collections::hash_map::RandomState;
+
+#[test]
+fn grows_past_initial_capacity() {
+    let mut sbf = ScalableBloomFilter::with_hasher(100, 0.01, RandomState::new());
+    assert_eq!(sbf.num_stages(), 1);
+    for i in 0..5_000u32 {
+        sbf.insert(&i);
+    }
+    println!("stages after 5000 inserts: {}", sbf.num_stages());
+    assert!(sbf.num_stages() > 1);
+    assert!(sbf.capacity() >= 5_000);
+    assert_eq!(sbf.approximate_items(), 5_000);
+    for i in 0..5_000u32 {
+        assert!(sbf.contains(&i), "missing {}", i);
+    }
+}
+
+#[test]
+fn compound_false_positive_rate_bounded() {
+    let p = 0.01;
+    let mut sbf = ScalableBloomFilter::with_hasher(500, p, RandomState::new());
+    let n = 20_000u64;
+    for i in 0..n {
+        sbf.insert(&i);
+    }
+    assert!(sbf.error_bound() < p);
+
+    let trials = 20_000u64;
+    let fp = (n..n + trials).filter(|i| sbf.contains(i)).count();
+    let rate = fp as f64 / trials as f64;
+    println!("Observed compound FP: {} / {} = {:.4}", fp, trials, rate);
+    assert!(rate <= p * 2.0, "false positive rate too high: {}", rate);
+}
+
+#[test]
+fn chain_serialization_roundtrip() {
+    let rs = RandomState::new();
+    let mut sbf = ScalableBloomFilter::with_hasher(64, 0.01, rs.clone());
+    for i in 0..1_000u32 {
+        sbf.insert(&i);
+    }
+    let bytes = sbf.to_bytes();
+    let restored = ScalableBloomFilter::from_bytes_hasher(&bytes, rs.clone()).expect("deserialize");
+    assert_eq!(restored.num_stages(), sbf.num_stages());
+    assert_eq!(restored.approximate_items(), sbf.approximate_items());
+    for i in 0..1_000u32 {
+        assert!(restored.contains(&i), "missing {}", i);
+    }
+    assert!(ScalableBloomFilter::from_bytes_hasher(&bytes[..bytes.len() - 1], rs).is_none());
+}
+
+#[test]
+fn clear_resets_to_single_stage() {
+    let mut sbf = ScalableBloomFilter::new(10, 0.05);
+    for i in 0..200u32 {
+        sbf.insert(&i);
+    }
+    sbf.clear();
+    assert_eq!(sbf.num_stages(), 1);
+    assert_eq!(sbf.approximate_items(), 0);
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn serde_json_roundtrip() {
+    use std::hash::BuildHasherDefault;
+    use std::collections::hash_map::DefaultHasher;
+
+    let mut sbf = ScalableBloomFilter::with_hasher(32, 0.01, BuildHasherDefault::<DefaultHasher>::default());
+    for i in 0..300u32 {
+        sbf.insert(&i);
+    }
+    let json = serde_json::to_string(&sbf).unwrap();
+    let restored: ScalableBloomFilter<BuildHasherDefault<DefaultHasher>> = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored.num_stages(), sbf.num_stages());
+    for i in 0..300u32 {
+        assert!(restored.contains(&i));
+    }
+}
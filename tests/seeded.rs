@@ -0,0 +1,91 @@
+use bloomz::{BloomFilter, PersistentHasher, SeededSip13, SipHasher13};
+use std::collections::hash_map::{DefaultHasher, RandomState};
+use std::hash::{BuildHasher, Hasher};
+
+#[test]
+fn sip13_matches_std_default_hasher() {
+    // std's DefaultHasher is SipHash-1-3 keyed with (0, 0)
+    let data: Vec<u8> = (0..64).collect();
+    for len in 0..data.len() {
+        let mut ours = SipHasher13::new_with_keys(0, 0);
+        let mut std = DefaultHasher::new();
+        // split writes exercise the tail buffer
+        let (a, b) = data[..len].split_at(len / 3);
+        ours.write(a);
+        ours.write(b);
+        std.write(&data[..len]);
+        assert_eq!(ours.finish(), std.finish(), "length {}", len);
+    }
+}
+
+#[test]
+fn seeded_output_is_fixed() {
+    // golden values: these must never change, or persisted filters break
+    let b = SeededSip13::with_seed(42);
+    assert_eq!(b.hash_one("bloomz"), 0xe43c_3a68_8e7c_3dde);
+    assert_eq!(b.hash_one(12345u64), 0xac3c_757f_c626_891a);
+    assert_eq!(b.hash_one(&[1u32, 2, 3][..]), 0x01a9_8907_e7e4_e0a7);
+
+    // integers are fed as little-endian bytes, usize as 64 bits
+    let mut bytes = SipHasher13::new_with_keys(1, 2);
+    bytes.write(&0x0102u16.to_le_bytes());
+    let mut int = SipHasher13::new_with_keys(1, 2);
+    int.write_u16(0x0102);
+    assert_eq!(int.finish(), bytes.finish());
+    assert_eq!(b.hash_one(7usize), b.hash_one(7u64));
+
+    assert_ne!(SeededSip13::with_seed(1).hash_one("x"), SeededSip13::with_seed(2).hash_one("x"));
+}
+
+#[test]
+fn filter_reloads_from_stored_seed() {
+    let hasher = SeededSip13::with_seed(0xfeed);
+    let mut bf = BloomFilter::with_hasher(20_000, 5, hasher);
+    for i in 0..1_000u32 {
+        bf.insert(&i);
+    }
+    let seed = bf.hasher_builder().seed();
+    let bytes = bf.to_bytes();
+
+    let restored = BloomFilter::from_bytes_hasher(&bytes, SeededSip13::from_seed(seed)).unwrap();
+    assert!((0..1_000u32).all(|i| restored.contains(&i)));
+    assert_eq!(SeededSip13::ID, 1);
+    assert_eq!(RandomState::ID, 0);
+}
+
+#[cfg(feature = "fast-xxh3")]
+#[test]
+fn xxh3_matches_reference() {
+    use bloomz::SeededXxh3;
+    let b = SeededXxh3::with_seed(99);
+    let mut h = b.build_hasher();
+    h.write(b"hello world");
+    assert_eq!(h.finish(), xxhash_rust::xxh3::xxh3_64_with_seed(b"hello world", 99));
+    assert_eq!(b.hash_one(5u32), xxhash_rust::xxh3::xxh3_64_with_seed(&5u32.to_le_bytes(), 99));
+}
+
+#[cfg(feature = "fast-ahash")]
+#[test]
+fn ahash_is_deterministic_per_seed() {
+    use bloomz::SeededAHash;
+    assert_eq!(SeededAHash::with_seed(3).hash_one("k"), SeededAHash::from_seed(3).hash_one("k"));
+    assert_ne!(SeededAHash::with_seed(3).hash_one("k"), SeededAHash::with_seed(4).hash_one("k"));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_restores_seed() {
+    let mut bf = BloomFilter::with_hasher(4_096, 4, SeededSip13::with_seed(7));
+    bf.insert(&"alpha");
+    let json = serde_json::to_string(&bf).unwrap();
+    let restored: BloomFilter<SeededSip13> = serde_json::from_str(&json).unwrap();
+    assert!(restored.contains(&"alpha"));
+    assert_eq!(restored.hasher_builder().seed(), 7);
+
+    // wrong hasher type is rejected instead of answering garbage
+    assert!(serde_json::from_str::<BloomFilter<RandomState>>(&json).is_err());
+
+    // data written before the hasher fields existed still loads
+    let legacy = r#"{"m":64,"k":1,"items":0,"words":[0]}"#;
+    assert!(serde_json::from_str::<BloomFilter<RandomState>>(legacy).is_ok());
+}
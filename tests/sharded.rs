@@ -0,0 +1,112 @@
+use bloomz::{BloomFilter, IndexStrategy, SeededSip13, ShardedBloomFilter};
+use std::collections::hash_map::RandomState;
+use std::thread;
+
+#[test]
+fn concurrent_writers_and_even_routing() {
+    let filter = ShardedBloomFilter::new_for_capacity(8, 80_000, 0.01);
+    thread::scope(|s| {
+        for t in 0..4u64 {
+            let filter = &filter;
+            s.spawn(move || {
+                for i in 0..20_000u64 {
+                    filter.insert(&(t * 1_000_000 + i));
+                }
+            });
+        }
+    });
+    assert_eq!(filter.approximate_items(), 80_000);
+    for t in 0..4u64 {
+        assert!((0..20_000u64).all(|i| filter.contains(&(t * 1_000_000 + i))));
+    }
+
+    let stats = filter.stats();
+    for (i, st) in stats.iter().enumerate() {
+        println!("shard {}: {} items, fill {:.3}, fpr {:.4}", i, st.items, st.fill_ratio, st.current_fpr);
+        assert!(st.items > 9_000 && st.items < 11_000);
+        assert!(st.current_fpr < 0.015);
+    }
+}
+
+#[test]
+fn shards_roundtrip_independently() {
+    let rs = RandomState::new();
+    let filter = ShardedBloomFilter::with_hasher(4, 20_000, 5, rs.clone());
+    let keys: Vec<String> = (0..4_000).map(|i| format!("user-{}", i)).collect();
+    for k in &keys {
+        filter.insert(k);
+    }
+
+    let blobs: Vec<Vec<u8>> = (0..4).map(|i| filter.shard_to_bytes(i)).collect();
+    let shards = blobs
+        .iter()
+        .map(|b| BloomFilter::from_bytes_hasher(b, rs.clone()).unwrap())
+        .collect();
+    let restored = ShardedBloomFilter::from_shards(shards, rs.clone());
+    assert!(keys.iter().all(|k| restored.contains(k)));
+
+    // a single shard can be shipped and swapped in elsewhere
+    let fresh = ShardedBloomFilter::with_hasher(4, 20_000, 5, rs.clone());
+    let target = fresh.shard_for(&keys[0]);
+    fresh.replace_shard(target, BloomFilter::from_bytes_hasher(&blobs[target], rs).unwrap());
+    let (routed, other): (Vec<&String>, Vec<&String>) = keys.iter().partition(|k| fresh.shard_for(k) == target);
+    assert!(routed.iter().all(|k| fresh.contains(k)));
+    assert!(other.iter().all(|k| !fresh.contains(k)));
+}
+
+#[test]
+fn merge_into_single_filter() {
+    let filter = ShardedBloomFilter::new(4, 64_000, 4);
+    for i in 0..2_000u32 {
+        filter.insert(&i);
+    }
+    let merged = filter.merge();
+    assert_eq!(merged.num_bits(), 64_000);
+    assert_eq!(merged.approximate_items(), 2_000);
+    assert!((0..2_000u32).all(|i| merged.contains(&i)));
+
+    filter.clear();
+    assert_eq!(filter.approximate_items(), 0);
+    assert!(filter.stats().iter().all(|s| s.ones == 0));
+}
+
+#[test]
+fn routing_keeps_fpr_for_every_strategy() {
+    // 8 shards sized for 10k keys each at 1%; routing on the probe bits gave ~1.5% here
+    let (shards, n, queries) = (8, 80_000u64, 200_000u64);
+    let strategies = [
+        IndexStrategy::DoubleHashing,
+        IndexStrategy::FastRange,
+        IndexStrategy::EnhancedDoubleHashing,
+        IndexStrategy::TripleHashing,
+    ];
+    for strategy in strategies {
+        let hasher = SeededSip13::with_seed(11);
+        let parts = (0..shards)
+            .map(|_| BloomFilter::with_hasher_and_strategy(95_851, 7, hasher, strategy))
+            .collect();
+        let filter = ShardedBloomFilter::from_shards(parts, hasher);
+        for i in 0..n {
+            filter.insert(&i);
+        }
+        let fp = (n..n + queries).filter(|i| filter.contains(i)).count();
+        let fpr = fp as f64 / queries as f64;
+        println!("{:?}: fpr {:.4}", strategy, fpr);
+        assert!(fpr < 0.012, "{:?} fpr {} too high", strategy, fpr);
+    }
+}
+
+#[test]
+#[should_panic(expected = "hasher mismatch between shards")]
+fn from_shards_rejects_a_different_hasher() {
+    let shards = (0..2).map(|_| BloomFilter::with_hasher(1_000, 3, SeededSip13::with_seed(1))).collect();
+    ShardedBloomFilter::from_shards(shards, SeededSip13::with_seed(2));
+}
+
+#[test]
+#[should_panic(expected = "index strategy mismatch for shard")]
+fn replace_shard_rejects_a_different_strategy() {
+    let hasher = SeededSip13::with_seed(1);
+    let filter = ShardedBloomFilter::with_hasher(2, 1_000, 3, hasher);
+    filter.replace_shard(0, BloomFilter::with_hasher_and_strategy(1_000, 3, hasher, IndexStrategy::FastRange));
+}
@@ -0,0 +1,70 @@
+use bloomz::{math, BloomFilter, StableBloomFilter};
+use std::collections::hash_map::RandomState;
+
+#[test]
+fn fpr_converges_to_stable_point() {
+    let mut filter = StableBloomFilter::for_fpr_with_hasher(32 * 1024, 3, 0.01, RandomState::new());
+    for i in 0..300_000u64 {
+        filter.insert(&i);
+    }
+    let trials = 100_000u64;
+    let fp = (10_000_000..10_000_000 + trials).filter(|i| filter.contains(i)).count();
+    let rate = fp as f64 / trials as f64;
+    println!(
+        "cells {} k {} P {}: stable {:.4}, current {:.4}, observed {:.4}",
+        filter.num_cells(),
+        filter.num_hashes(),
+        filter.decrements(),
+        filter.stable_fpr(),
+        filter.current_fpr(),
+        rate
+    );
+    assert!(filter.stable_fpr() <= 0.01);
+    assert!(rate < 0.015, "false positive rate too high: {}", rate);
+    assert!((filter.current_fpr() - filter.stable_fpr()).abs() < 0.005);
+}
+
+#[test]
+fn does_not_saturate_like_bloom() {
+    let bytes = 16 * 1024;
+    let mut stable = StableBloomFilter::new(bytes, 0.02);
+    let mut bloom = BloomFilter::new(bytes * 8, 5);
+    for i in 0..200_000u64 {
+        stable.insert(&i);
+        bloom.insert(&i);
+    }
+    let fresh = 1_000_000..1_010_000u64;
+    let stable_fp = fresh.clone().filter(|i| stable.contains(i)).count();
+    let bloom_fp = fresh.filter(|i| bloom.contains(i)).count();
+    println!("after 200k inserts: stable {} / bloom {} false positives of 10000", stable_fp, bloom_fp);
+    assert!(bloom_fp > 9_900);
+    assert!(stable_fp < 300);
+}
+
+#[test]
+fn recent_items_are_remembered() {
+    let mut filter = StableBloomFilter::new(8 * 1024, 0.01);
+    let mut forgotten = 0;
+    for i in 0..200_000u64 {
+        filter.insert(&i);
+        assert!(filter.check_and_insert(&i), "immediate repeat of {} missed", i);
+        if i >= 20 && !filter.contains(&(i - 20)) {
+            forgotten += 1;
+        }
+    }
+    println!("items forgotten after 20 inserts: {}", forgotten);
+    assert_eq!(forgotten, 0);
+}
+
+#[test]
+fn decrements_invert_stable_fpr() {
+    let (m, d) = (100_000, 3);
+    for p in [0.1, 0.01, 0.001] {
+        let k = math::stable_k(p);
+        let decrements = math::stable_decrements(m, k, d, p);
+        assert!(math::stable_fpr(m, k, d, decrements) <= p);
+        if decrements > 1 {
+            assert!(math::stable_fpr(m, k, d, decrements - 1) > p);
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use bloomz::{BloomFilter, BloomFilterRef, FormatError, SeededSip13};
+use std::collections::hash_map::RandomState;
+
+#[test]
+fn view_answers_like_owned_filter() {
+    let rs = RandomState::new();
+    let mut bf = BloomFilter::with_hasher(50_000, 6, rs.clone());
+    for i in 0..5_000u32 {
+        bf.insert(&i);
+    }
+    let bytes = bf.to_bytes();
+    let view = BloomFilterRef::from_bytes_hasher(&bytes, rs).unwrap();
+    assert_eq!((view.num_bits(), view.num_hashes()), (50_000, 6));
+    let mut fp = 0;
+    for i in 0..20_000u32 {
+        assert_eq!(view.contains(&i), bf.contains(&i), "item {}", i);
+        if i >= 5_000 && view.contains(&i) {
+            fp += 1;
+        }
+    }
+    println!("view false positives: {} / 15000", fp);
+}
+
+#[test]
+fn view_inside_larger_unaligned_blob() {
+    let mut bf = BloomFilter::for_capacity_with_hasher(2_000, 0.01, SeededSip13::with_seed(5));
+    for i in 0..2_000u64 {
+        bf.insert(&i);
+    }
+    // embed at an odd offset so the payload is not 8-byte aligned
+    let mut blob = vec![0xaa; 3];
+    blob.extend_from_slice(&bf.to_versioned_bytes());
+    blob.extend_from_slice(b"trailing section");
+    let end = blob.len() - 16;
+
+    let view = BloomFilterRef::<SeededSip13>::from_versioned_bytes(&blob[3..end]).unwrap();
+    assert_eq!(view.approximate_items(), 2_000);
+    assert!((0..2_000u64).all(|i| view.contains(&i)));
+
+    let owned = view.to_bloom_filter();
+    assert_eq!(owned.approximate_items(), 2_000);
+    assert!((0..2_000u64).all(|i| owned.contains(&i)));
+}
+
+#[test]
+fn view_validates_once() {
+    let bf = BloomFilter::with_hasher(1_024, 3, SeededSip13::with_seed(8));
+    let mut bytes = bf.to_versioned_bytes();
+    assert!(BloomFilterRef::from_versioned_bytes_hasher(&bytes, SeededSip13::with_seed(8)).is_ok());
+    let last = bytes.len() - 1;
+    bytes[last] ^= 1;
+    assert_eq!(
+        BloomFilterRef::<SeededSip13>::from_versioned_bytes(&bytes).unwrap_err(),
+        FormatError::BadChecksum
+    );
+    assert!(BloomFilterRef::from_bytes_hasher(&[0u8; 11], RandomState::new()).is_none());
+}